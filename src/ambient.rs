@@ -0,0 +1,295 @@
+use std::f32::consts::{PI, TAU};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use rodio::Source;
+use crate::audio;
+use crate::story::Layer;
+use crate::synth::SAMPLE_RATE;
+
+// How long a layer takes to fade fully in or out, so a change of scene
+// crossfades from one soundscape to the next
+const FADE: Duration = Duration::from_secs(3);
+
+// The gain a layer is heading for: set by the game, and followed sample by
+// sample on the audio thread
+struct Fader(AtomicU32);
+
+impl Fader {
+    fn new(target: f32) -> Fader {
+        Fader(AtomicU32::new(target.to_bits()))
+    }
+
+    fn target(&self) -> f32 {
+        f32::from_bits(self.0.load(Ordering::Relaxed))
+    }
+
+    fn set(&self, target: f32) {
+        self.0.store(target.to_bits(), Ordering::Relaxed);
+    }
+}
+
+static PLAYING: Mutex<Vec<(Layer, Arc<Fader>)>> = Mutex::new(Vec::new());
+
+// Fade in each of `layers` that isn't already playing, and fade out the rest
+pub fn set(layers: &[Layer]) {
+    let mut playing = PLAYING.lock().unwrap();
+    playing.retain(|(layer, fader)| {
+        let keep = layers.contains(layer);
+        if !keep {
+            fader.set(0.0);
+        }
+        keep
+    });
+    for layer in layers {
+        if !playing.iter().any(|(p, _)| p == layer) {
+            let fader = Arc::new(Fader::new(1.0));
+            audio::mix(Box::new(Ambient::new(*layer, fader.clone())));
+            playing.push((*layer, fader));
+        }
+    }
+}
+
+// Cheap noise for the audio thread. It has its own seed rather than drawing
+// from the game's generator, so ambience never changes a seeded run.
+struct Noise(u32);
+
+impl Noise {
+    // -1.0 to 1.0
+    fn next(&mut self) -> f32 {
+        self.unit() * 2.0 - 1.0
+    }
+
+    // 0.0 to 1.0
+    fn unit(&mut self) -> f32 {
+        let mut x = self.0;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.0 = x;
+        x as f32 / u32::MAX as f32
+    }
+
+    fn between(&mut self, low: f32, high: f32) -> f32 {
+        low + self.unit() * (high - low)
+    }
+}
+
+fn samples(ms: f32) -> usize {
+    (ms / 1000.0 * SAMPLE_RATE as f32) as usize
+}
+
+// Breeze: noise through a low-pass filter whose cutoff and level drift
+// slowly, so it rises and falls in gusts
+struct Wind {
+    low: f32,
+    lower: f32,
+    // Two slow cycles, kept wrapped so they stay precise however long it blows
+    swell: f32,
+    flurry: f32,
+}
+
+impl Wind {
+    fn sample(&mut self, noise: &mut Noise) -> f32 {
+        self.swell = (self.swell + 0.07 / SAMPLE_RATE as f32) % 1.0;
+        self.flurry = (self.flurry + 0.19 / SAMPLE_RATE as f32) % 1.0;
+        let gust = (TAU * self.swell).sin() * 0.5 + (TAU * self.flurry).sin() * 0.3;
+        let cutoff = 0.02 + 0.012 * gust;
+        self.low += cutoff * (noise.next() - self.low);
+        self.lower += cutoff * (self.low - self.lower);
+        self.lower * (1.2 + 0.6 * gust)
+    }
+}
+
+// Footsteps in grass: every half second or so a heel and then a toe land,
+// each a short burst of crackle that dies away quickly
+struct Grass {
+    wait: usize,
+    envelope: f32,
+    heel: bool,
+    low: f32,
+}
+
+impl Grass {
+    fn sample(&mut self, noise: &mut Noise) -> f32 {
+        if self.wait == 0 {
+            if self.heel {
+                self.envelope = 1.0;
+                self.wait = samples(70.0);
+            } else {
+                self.envelope = 0.7;
+                self.wait = samples(noise.between(480.0, 640.0));
+            }
+            self.heel = !self.heel;
+        }
+        self.wait -= 1;
+        self.envelope *= 0.9993;
+
+        // Sparse clicks, high-passed, are the blades snapping
+        let crackle = if noise.unit() < 0.35 * self.envelope { noise.next() } else { 0.0 };
+        self.low += 0.1 * (crackle - self.low);
+        (crackle - self.low) * self.envelope * 0.25
+    }
+}
+
+// How a chirping creature calls: a few quick sweeps, then quiet
+struct Call {
+    // Hz
+    pitch: (f32, f32),
+    // Each note ends at this multiple of the pitch it started at
+    sweep: f32,
+    note_ms: f32,
+    gap_ms: f32,
+    notes: (u32, u32),
+    interval_ms: (f32, f32),
+    level: f32,
+}
+
+// A shrill bird somewhere in the trees
+const BIRD: Call = Call {
+    pitch: (2400.0, 3600.0),
+    sweep: 1.5,
+    note_ms: 90.0,
+    gap_ms: 60.0,
+    notes: (2, 5),
+    interval_ms: (1500.0, 5000.0),
+    level: 0.12,
+};
+
+// Insects in the grass: tiny, fast, steady trills
+const INSECT: Call = Call {
+    pitch: (4300.0, 4700.0),
+    sweep: 1.0,
+    note_ms: 18.0,
+    gap_ms: 14.0,
+    notes: (3, 4),
+    interval_ms: (250.0, 700.0),
+    level: 0.04,
+};
+
+struct Chirps {
+    call: &'static Call,
+    wait: usize,
+    notes_left: u32,
+    position: usize,
+    length: usize,
+    from: f32,
+    to: f32,
+    phase: f32,
+}
+
+impl Chirps {
+    fn new(call: &'static Call) -> Chirps {
+        Chirps {
+            call,
+            wait: samples(call.interval_ms.0),
+            notes_left: 0,
+            position: 0,
+            length: 0,
+            from: 0.0,
+            to: 0.0,
+            phase: 0.0,
+        }
+    }
+
+    fn sample(&mut self, noise: &mut Noise) -> f32 {
+        if self.position < self.length {
+            let t = self.position as f32 / self.length as f32;
+            let frequency = self.from + (self.to - self.from) * t;
+            self.phase = (self.phase + frequency / SAMPLE_RATE as f32) % 1.0;
+            self.position += 1;
+            if self.position == self.length {
+                self.wait = if self.notes_left > 0 {
+                    samples(self.call.gap_ms)
+                } else {
+                    samples(noise.between(self.call.interval_ms.0, self.call.interval_ms.1))
+                };
+            }
+            return (TAU * self.phase).sin() * (PI * t).sin() * self.call.level;
+        }
+        if self.wait > 0 {
+            self.wait -= 1;
+            return 0.0;
+        }
+
+        // Start the next note, and a new call if the last one is over
+        if self.notes_left == 0 {
+            let (fewest, most) = self.call.notes;
+            self.notes_left = noise.between(fewest as f32, most as f32 + 1.0) as u32;
+        }
+        self.notes_left = self.notes_left.saturating_sub(1);
+        self.from = noise.between(self.call.pitch.0, self.call.pitch.1);
+        self.to = self.from * self.call.sweep;
+        self.length = samples(self.call.note_ms);
+        self.position = 0;
+        0.0
+    }
+}
+
+enum Generator {
+    Wind(Wind),
+    Grass(Grass),
+    Chirps(Chirps),
+}
+
+// One looping layer, fading toward its fader's target; it ends once faded out
+struct Ambient {
+    generator: Generator,
+    noise: Noise,
+    fader: Arc<Fader>,
+    gain: f32,
+}
+
+impl Ambient {
+    fn new(layer: Layer, fader: Arc<Fader>) -> Ambient {
+        let (generator, seed) = match layer {
+            Layer::Wind => (Generator::Wind(Wind { low: 0.0, lower: 0.0, swell: 0.0, flurry: 0.0 }), 0x9e37_79b9),
+            Layer::Grass => (Generator::Grass(Grass { wait: 0, envelope: 0.0, heel: true, low: 0.0 }), 0x85eb_ca6b),
+            Layer::Birds => (Generator::Chirps(Chirps::new(&BIRD)), 0xc2b2_ae35),
+            Layer::Insects => (Generator::Chirps(Chirps::new(&INSECT)), 0x27d4_eb2f),
+        };
+        Ambient { generator, noise: Noise(seed), fader, gain: 0.0 }
+    }
+}
+
+impl Iterator for Ambient {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let target = self.fader.target();
+        let step = 1.0 / (FADE.as_secs_f32() * SAMPLE_RATE as f32);
+        self.gain = if self.gain < target {
+            (self.gain + step).min(target)
+        } else {
+            (self.gain - step).max(target)
+        };
+        if target == 0.0 && self.gain == 0.0 {
+            return None;
+        }
+
+        let sample = match &mut self.generator {
+            Generator::Wind(wind) => wind.sample(&mut self.noise),
+            Generator::Grass(grass) => grass.sample(&mut self.noise),
+            Generator::Chirps(chirps) => chirps.sample(&mut self.noise),
+        };
+        Some(sample * self.gain)
+    }
+}
+
+impl Source for Ambient {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        SAMPLE_RATE
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
@@ -0,0 +1,78 @@
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use rodio::{OutputStream, Sink, Source};
+
+// Anything the engine can play: mono or stereo f32 samples
+pub type Sound = Box<dyn Source<Item = f32> + Send>;
+
+enum Command {
+    // Queue sounds to play one after another, after anything already queued
+    Play(Vec<Sound>),
+    // Play a sound at once, mixed over everything else, e.g. an ambient layer
+    Mix(Sound),
+    // Reply once everything queued so far has played
+    Wait(Sender<()>),
+}
+
+// The engine's thread owns the output stream, which can't leave the thread
+// that opened it, so everyone else talks to it over a channel
+static ENGINE: OnceLock<Mutex<Sender<Command>>> = OnceLock::new();
+
+fn engine() -> &'static Mutex<Sender<Command>> {
+    ENGINE.get_or_init(|| {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            // Without an audio device sounds are dropped, and waits return at once
+            let device = OutputStream::try_default().ok().and_then(|(stream, handle)| {
+                Sink::try_new(&handle).ok().map(|sink| (stream, handle, sink))
+            });
+            for command in receiver {
+                match command {
+                    Command::Play(sounds) => {
+                        if let Some((_, _, sink)) = &device {
+                            for sound in sounds {
+                                sink.append(sound);
+                            }
+                        }
+                    }
+                    Command::Mix(sound) => {
+                        if let Some((_, handle, _)) = &device {
+                            let _ = handle.play_raw(sound);
+                        }
+                    }
+                    Command::Wait(reply) => {
+                        if let Some((_, _, sink)) = &device {
+                            sink.sleep_until_end();
+                        }
+                        let _ = reply.send(());
+                    }
+                }
+            }
+        });
+        Mutex::new(sender)
+    })
+}
+
+// Queue sounds to play back to back, without waiting for them
+pub fn play(sounds: Vec<Sound>) {
+    let _ = engine().lock().unwrap().send(Command::Play(sounds));
+}
+
+// Play a sound straight away, alongside whatever else is playing. Not
+// waited for by `wait`, so a sound that never ends is fine.
+pub fn mix(sound: Sound) {
+    let _ = engine().lock().unwrap().send(Command::Mix(sound));
+}
+
+// Block until everything queued has played, e.g. before the program exits
+pub fn wait() {
+    // Nothing to wait for if nothing has played
+    let Some(engine) = ENGINE.get() else {
+        return;
+    };
+    let (reply, done) = mpsc::channel();
+    if engine.lock().unwrap().send(Command::Wait(reply)).is_ok() {
+        let _ = done.recv();
+    }
+}
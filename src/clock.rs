@@ -0,0 +1,66 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+// In countdown mode the hours that remain are also a wall-clock timer: each
+// in-game hour lasts a fixed stretch of real time, and the story spending
+// hours brings the deadline closer
+struct Countdown {
+    per_hour: Duration,
+    deadline: Instant,
+}
+
+static COUNTDOWN: Mutex<Option<Countdown>> = Mutex::new(None);
+
+// Start (or restart, e.g. on loading a save) the countdown with `hours` left
+pub fn start(per_hour: Duration, hours: i32) {
+    let deadline = Instant::now() + per_hour * hours.max(0) as u32;
+    *COUNTDOWN.lock().unwrap() = Some(Countdown { per_hour, deadline });
+}
+
+pub fn restart(hours: i32) {
+    let per_hour = COUNTDOWN.lock().unwrap().as_ref().map(|c| c.per_hour);
+    if let Some(per_hour) = per_hour {
+        start(per_hour, hours);
+    }
+}
+
+// Real time left, or None outside countdown mode
+pub fn remaining() -> Option<Duration> {
+    let countdown = COUNTDOWN.lock().unwrap();
+    countdown.as_ref().map(|c| c.deadline.saturating_duration_since(Instant::now()))
+}
+
+pub fn expired() -> bool {
+    remaining() == Some(Duration::ZERO)
+}
+
+// Whole in-game hours left on the clock, rounded up
+pub fn hours_left() -> Option<i32> {
+    let countdown = COUNTDOWN.lock().unwrap();
+    countdown.as_ref().map(|c| {
+        let left = c.deadline.saturating_duration_since(Instant::now());
+        (left.as_secs_f64() / c.per_hour.as_secs_f64()).ceil() as i32
+    })
+}
+
+// In-game time left, in seconds, for a clock that runs at game speed
+pub fn game_seconds_left() -> Option<u64> {
+    let countdown = COUNTDOWN.lock().unwrap();
+    countdown.as_ref().map(|c| {
+        let left = c.deadline.saturating_duration_since(Instant::now());
+        (left.as_secs_f64() / c.per_hour.as_secs_f64() * 3600.0) as u64
+    })
+}
+
+// Move the deadline when the story changes the hours, e.g. -3 for a scene
+// that costs three hours
+pub fn adjust(hours: i32) {
+    if let Some(c) = COUNTDOWN.lock().unwrap().as_mut() {
+        let shift = c.per_hour * hours.unsigned_abs();
+        c.deadline = if hours < 0 {
+            c.deadline.checked_sub(shift).unwrap_or_else(Instant::now)
+        } else {
+            c.deadline + shift
+        };
+    }
+}
@@ -0,0 +1,367 @@
+use std::fmt;
+use serde::Deserialize;
+use crate::state::{GameState, Value};
+
+// A condition over the game state, written in story files as an expression:
+//
+//   hours >= 3
+//   !stand && visited(who_am_i)
+//   name == "Eve" || (hours < 6 && !who)
+//
+// Operators, loosest first: ||, &&, comparisons (== != < <= > >=), then !.
+// Bare variables are true when they are true, non-zero or non-empty.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Condition {
+    source: String,
+    expr: Expr,
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Literal(Value),
+    Var(String),
+    Visited(String),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Compare(Box<Expr>, CompareOp, Box<Expr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+// Something a condition depends on, for checking against a story
+pub enum Reference<'a> {
+    Var(&'a str),
+    Scene(&'a str),
+}
+
+impl Condition {
+    pub fn parse(source: &str) -> Result<Condition, String> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser { tokens, pos: 0 };
+        let expr = parser.or()?;
+        if let Some(token) = parser.peek() {
+            return Err(format!("unexpected {} in '{}'", token, source));
+        }
+        Ok(Condition { source: source.to_string(), expr })
+    }
+
+    pub fn eval(&self, state: &GameState) -> bool {
+        truthy(&self.expr.eval(state))
+    }
+
+    pub fn references(&self) -> Vec<Reference<'_>> {
+        let mut found = Vec::new();
+        self.expr.references(&mut found);
+        found
+    }
+}
+
+impl TryFrom<String> for Condition {
+    type Error = String;
+
+    fn try_from(source: String) -> Result<Condition, String> {
+        Condition::parse(&source)
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+impl Expr {
+    fn eval(&self, state: &GameState) -> Value {
+        match self {
+            Expr::Literal(value) => value.clone(),
+            // Unknown variables read as false
+            Expr::Var(name) => state.get(name).cloned().unwrap_or(Value::Bool(false)),
+            Expr::Visited(scene) => Value::Bool(state.has_visited(scene)),
+            Expr::Not(inner) => Value::Bool(!truthy(&inner.eval(state))),
+            Expr::And(a, b) => Value::Bool(truthy(&a.eval(state)) && truthy(&b.eval(state))),
+            Expr::Or(a, b) => Value::Bool(truthy(&a.eval(state)) || truthy(&b.eval(state))),
+            Expr::Compare(a, op, b) => Value::Bool(compare(&a.eval(state), *op, &b.eval(state))),
+        }
+    }
+
+    fn references<'a>(&'a self, found: &mut Vec<Reference<'a>>) {
+        match self {
+            Expr::Literal(_) => {}
+            Expr::Var(name) => found.push(Reference::Var(name)),
+            Expr::Visited(scene) => found.push(Reference::Scene(scene)),
+            Expr::Not(inner) => inner.references(found),
+            Expr::And(a, b) | Expr::Or(a, b) | Expr::Compare(a, _, b) => {
+                a.references(found);
+                b.references(found);
+            }
+        }
+    }
+}
+
+fn truthy(value: &Value) -> bool {
+    match value {
+        Value::Bool(b) => *b,
+        Value::Int(n) => *n != 0,
+        Value::Str(s) => !s.is_empty(),
+    }
+}
+
+fn compare(a: &Value, op: CompareOp, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Int(a), Value::Int(b)) => match op {
+            CompareOp::Eq => a == b,
+            CompareOp::Ne => a != b,
+            CompareOp::Lt => a < b,
+            CompareOp::Le => a <= b,
+            CompareOp::Gt => a > b,
+            CompareOp::Ge => a >= b,
+        },
+        // Other types only compare for equality
+        _ => match op {
+            CompareOp::Eq => a == b,
+            CompareOp::Ne => a != b,
+            _ => false,
+        },
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Int(i32),
+    Str(String),
+    Not,
+    And,
+    Or,
+    Compare(CompareOp),
+    Open,
+    Close,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Ident(name) => write!(f, "'{}'", name),
+            Token::Int(n) => write!(f, "'{}'", n),
+            Token::Str(s) => write!(f, "\"{}\"", s),
+            Token::Not => write!(f, "'!'"),
+            Token::And => write!(f, "'&&'"),
+            Token::Or => write!(f, "'||'"),
+            Token::Compare(_) => write!(f, "comparison"),
+            Token::Open => write!(f, "'('"),
+            Token::Close => write!(f, "')'"),
+        }
+    }
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        let (token, width) = match (c, next) {
+            (c, _) if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            ('&', Some('&')) => (Token::And, 2),
+            ('|', Some('|')) => (Token::Or, 2),
+            ('=', Some('=')) => (Token::Compare(CompareOp::Eq), 2),
+            ('!', Some('=')) => (Token::Compare(CompareOp::Ne), 2),
+            ('<', Some('=')) => (Token::Compare(CompareOp::Le), 2),
+            ('>', Some('=')) => (Token::Compare(CompareOp::Ge), 2),
+            ('<', _) => (Token::Compare(CompareOp::Lt), 1),
+            ('>', _) => (Token::Compare(CompareOp::Gt), 1),
+            ('!', _) => (Token::Not, 1),
+            ('(', _) => (Token::Open, 1),
+            (')', _) => (Token::Close, 1),
+            ('"', _) => {
+                let end = chars[i + 1..]
+                    .iter()
+                    .position(|&c| c == '"')
+                    .ok_or_else(|| format!("unterminated string in '{}'", source))?;
+                let text: String = chars[i + 1..i + 1 + end].iter().collect();
+                (Token::Str(text), end + 2)
+            }
+            (c, _) if c.is_ascii_digit() || (c == '-' && next.is_some_and(|n| n.is_ascii_digit())) => {
+                let len = 1 + chars[i + 1..].iter().take_while(|c| c.is_ascii_digit()).count();
+                let text: String = chars[i..i + len].iter().collect();
+                let n = text.parse().map_err(|_| format!("'{}' is too large", text))?;
+                (Token::Int(n), len)
+            }
+            (c, _) if c.is_alphabetic() || c == '_' => {
+                let len = chars[i..].iter().take_while(|c| c.is_alphanumeric() || **c == '_').count();
+                (Token::Ident(chars[i..i + len].iter().collect()), len)
+            }
+            (c, _) => return Err(format!("unexpected '{}' in '{}'", c, source)),
+        };
+        tokens.push(token);
+        i += width;
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), String> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            Some(token) => Err(format!("expected {} but found {}", expected, token)),
+            None => Err(format!("expected {} but the condition ended", expected)),
+        }
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        let mut left = self.and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.next();
+            left = Expr::Or(Box::new(left), Box::new(self.and()?));
+        }
+        Ok(left)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut left = self.comparison()?;
+        while self.peek() == Some(&Token::And) {
+            self.next();
+            left = Expr::And(Box::new(left), Box::new(self.comparison()?));
+        }
+        Ok(left)
+    }
+
+    fn comparison(&mut self) -> Result<Expr, String> {
+        let left = self.unary()?;
+        if let Some(&Token::Compare(op)) = self.peek() {
+            self.next();
+            let right = self.unary()?;
+            return Ok(Expr::Compare(Box::new(left), op, Box::new(right)));
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        match self.next() {
+            Some(Token::Not) => Ok(Expr::Not(Box::new(self.unary()?))),
+            Some(Token::Open) => {
+                let inner = self.or()?;
+                self.expect(Token::Close)?;
+                Ok(inner)
+            }
+            Some(Token::Int(n)) => Ok(Expr::Literal(Value::Int(n))),
+            Some(Token::Str(s)) => Ok(Expr::Literal(Value::Str(s))),
+            Some(Token::Ident(name)) => match name.as_str() {
+                "true" => Ok(Expr::Literal(Value::Bool(true))),
+                "false" => Ok(Expr::Literal(Value::Bool(false))),
+                "visited" => {
+                    self.expect(Token::Open)?;
+                    let scene = match self.next() {
+                        Some(Token::Ident(scene)) => scene,
+                        _ => return Err("visited() takes a scene id".to_string()),
+                    };
+                    self.expect(Token::Close)?;
+                    Ok(Expr::Visited(scene))
+                }
+                _ => Ok(Expr::Var(name)),
+            },
+            Some(token) => Err(format!("unexpected {}", token)),
+            None => Err("the condition is incomplete".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use super::*;
+
+    fn state() -> GameState {
+        let vars = BTreeMap::from([
+            ("hours".to_string(), Value::Int(5)),
+            ("yes".to_string(), Value::Bool(true)),
+            ("no".to_string(), Value::Bool(false)),
+            ("name".to_string(), Value::Str("Eve".to_string())),
+        ]);
+        let mut state = GameState::new(&vars);
+        state.visit("awaken");
+        state
+    }
+
+    fn eval(source: &str) -> bool {
+        Condition::parse(source).unwrap().eval(&state())
+    }
+
+    fn error(source: &str) -> String {
+        Condition::parse(source).unwrap_err()
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        assert!(eval("yes || no && no"));
+        assert!(!eval("(yes || no) && no"));
+    }
+
+    #[test]
+    fn comparisons_bind_tighter_than_and() {
+        assert!(eval("hours > 2 && yes"));
+        assert!(eval("no || name == \"Eve\""));
+    }
+
+    #[test]
+    fn not_binds_tightest() {
+        // (!hours) == 0 compares false with 0, rather than negating hours == 0
+        assert!(!eval("!hours == 0"));
+        assert!(eval("!(hours == 0)"));
+        assert!(eval("!no && yes"));
+    }
+
+    #[test]
+    fn values_and_visits() {
+        assert!(eval("hours >= 5 && hours < 6 && hours != 4"));
+        assert!(eval("name != \"Adam\" && -1 < 0"));
+        assert!(eval("visited(awaken) && !visited(who_am_i)"));
+        // Unknown variables read as false, and only numbers are ordered
+        assert!(!eval("missing"));
+        assert!(!eval("name > \"A\""));
+    }
+
+    #[test]
+    fn errors_say_what_went_wrong() {
+        assert_eq!(error("!who && ("), "the condition is incomplete");
+        assert_eq!(error("(who"), "expected ')' but the condition ended");
+        assert_eq!(error("visited(who"), "expected ')' but the condition ended");
+        assert_eq!(error("visited(3)"), "visited() takes a scene id");
+        assert_eq!(error("who )"), "unexpected ')' in 'who )'");
+        assert_eq!(error("1 < 2 < 3"), "unexpected comparison in '1 < 2 < 3'");
+        assert_eq!(error("hours = 3"), "unexpected '=' in 'hours = 3'");
+        assert_eq!(error("name == \"Eve"), "unterminated string in 'name == \"Eve'");
+        assert_eq!(error("hours > 99999999999"), "'99999999999' is too large");
+    }
+}
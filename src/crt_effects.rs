@@ -1,9 +1,13 @@
 use std::io::{self, Result, Write};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
 use std::thread;
 use std::time::Duration;
 use rand::Rng;
+use crate::{render, rng};
 use crossterm::{
     cursor,
+    event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
     execute,
     style::{Color, Print, ResetColor, SetForegroundColor},
     terminal,
@@ -32,8 +36,109 @@ pub enum PhosphorType {
     Blue,
 }
 
+// How quickly typewriter text appears, for the whole session
+#[derive(Clone, Copy, PartialEq)]
+pub enum TextSpeed {
+    Instant,
+    Fast,
+    Normal,
+    Slow,
+}
+
+impl TextSpeed {
+    // Scale a delay written for normal speed
+    pub fn scale(self, delay_ms: u64) -> u64 {
+        match self {
+            TextSpeed::Instant => 0,
+            TextSpeed::Fast => delay_ms / 3,
+            TextSpeed::Normal => delay_ms,
+            TextSpeed::Slow => delay_ms * 2,
+        }
+    }
+}
+
+impl FromStr for TextSpeed {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<TextSpeed, String> {
+        match s.to_ascii_lowercase().as_str() {
+            "instant" => Ok(TextSpeed::Instant),
+            "fast" => Ok(TextSpeed::Fast),
+            "normal" => Ok(TextSpeed::Normal),
+            "slow" => Ok(TextSpeed::Slow),
+            _ => Err(format!("unknown text speed '{}', expected instant, fast, normal or slow", s)),
+        }
+    }
+}
+
+static TEXT_SPEED: AtomicU8 = AtomicU8::new(TextSpeed::Normal as u8);
+// Set when the player presses a key to finish the passage being typed out
+static SKIPPING: AtomicBool = AtomicBool::new(false);
+
+pub fn set_text_speed(speed: TextSpeed) {
+    TEXT_SPEED.store(speed as u8, Ordering::Relaxed);
+}
+
+pub fn text_speed() -> TextSpeed {
+    match TEXT_SPEED.load(Ordering::Relaxed) {
+        0 => TextSpeed::Instant,
+        1 => TextSpeed::Fast,
+        3 => TextSpeed::Slow,
+        _ => TextSpeed::Normal,
+    }
+}
+
+// Start typing out a new passage, e.g. a whole scene; a keypress during it
+// skips to the end
+pub fn begin_passage() {
+    SKIPPING.store(false, Ordering::Relaxed);
+}
+
+// Whether the rest of the current passage should appear at once, either
+// because text is instant or because a key was pressed since it began.
+// Ctrl-C isn't a skip: it cuts the link, as it does at a prompt.
+pub fn skipping() -> Result<bool> {
+    if SKIPPING.load(Ordering::Relaxed) || text_speed() == TextSpeed::Instant {
+        return Ok(true);
+    }
+    while event::poll(Duration::from_millis(0))? {
+        if let Event::Key(key) = event::read()? {
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+                return Err(render::disconnected());
+            }
+            SKIPPING.store(true, Ordering::Relaxed);
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+// How far the display has decayed, in percent: it follows the hours the
+// player has spent, so the terminal fails as their time does
+static DECAY: AtomicU8 = AtomicU8::new(0);
+
+pub fn set_decay(decay: f64) {
+    DECAY.store((decay.clamp(0.0, 1.0) * 100.0).round() as u8, Ordering::Relaxed);
+}
+
+// 0.0 with the whole stay ahead, 1.0 with none of it left
+pub fn decay() -> f64 {
+    DECAY.load(Ordering::Relaxed) as f64 / 100.0
+}
+
+// Sleep for a typewriter delay at the current text speed
+pub fn type_delay(delay_ms: u64) {
+    let delay_ms = text_speed().scale(delay_ms);
+    if delay_ms > 0 {
+        thread::sleep(Duration::from_millis(delay_ms));
+    }
+}
+
 // Get phosphor colors based on type
-fn get_phosphor_colors(phosphor_type: PhosphorType) -> (Color, Color, Color) {
+pub fn get_phosphor_colors(phosphor_type: PhosphorType) -> (Color, Color, Color) {
     match phosphor_type {
         PhosphorType::Green => (PHOSPHOR_BRIGHT, PHOSPHOR_MEDIUM, PHOSPHOR_DIM),
         PhosphorType::Amber => (AMBER_BRIGHT, AMBER_MEDIUM, AMBER_DIM),
@@ -114,6 +219,19 @@ pub fn print_slowly_with_phosphor(
     let mut current_x = x;
     
     for c in text.chars() {
+        // The rest of the passage appears at once in its final colour
+        if skipping()? {
+            execute!(
+                stdout,
+                cursor::MoveTo(x, y),
+                SetForegroundColor(Color::White),
+                Print(text),
+                ResetColor
+            )?;
+            println!();
+            return Ok(());
+        }
+
         // Print the current character with bright phosphor
         execute!(
             stdout,
@@ -158,11 +276,11 @@ pub fn print_slowly_with_phosphor(
         }
         
         current_x += 1;
-        thread::sleep(Duration::from_millis(char_delay_ms));
+        type_delay(char_delay_ms);
     }
     
     // Fade out the last few characters after completing the text
-    thread::sleep(Duration::from_millis(char_delay_ms));
+    type_delay(char_delay_ms);
     
     // Fade the last two characters
     for i in 0..std::cmp::min(3, text.len() as u16) {
@@ -180,7 +298,7 @@ pub fn print_slowly_with_phosphor(
         )?;
     }
     
-    thread::sleep(Duration::from_millis(char_delay_ms));
+    type_delay(char_delay_ms);
     
     // Final dim glow on last character
     for i in 0..std::cmp::min(3, text.len() as u16) {
@@ -197,7 +315,7 @@ pub fn print_slowly_with_phosphor(
         )?;
     }
     
-    thread::sleep(Duration::from_millis(char_delay_ms));
+    type_delay(char_delay_ms);
     
     // Return all characters to normal color
     execute!(
@@ -224,19 +342,26 @@ pub fn phosphor_noise(
     intensity: f32  // 0.0 to 1.0, how much noise to display
 ) -> Result<()> {
     let mut stdout = io::stdout();
-    let mut rng = rand::thread_rng();
+    let mut rng = rng::game_rng();
     let (bright, medium, dim) = get_phosphor_colors(phosphor_type);
     
     // Save cursor position
     execute!(stdout, cursor::SavePosition)?;
     
-    //let noise_chars = vec!['·', ':', '·', '`', '.', ' '];
+    // A healthy tube only flickers the colour under the cursor; a decaying one
+    // leaves stray glyphs behind
+    let noise_chars = ['·', ':', '`', '.'];
+    let glyph_chance = decay() / 2.0;
     
     // Generate random phosphor noise
     for _ in 0..((width * height) as f32 * intensity) as u16 {
         let noise_x = x + rng.gen_range(0..width);
         let noise_y = y + rng.gen_range(0..height);
-        //let noise_char = noise_chars[rng.gen_range(0..noise_chars.len())];
+        let noise_char = if glyph_chance > 0.0 && rng.gen_bool(glyph_chance) {
+            noise_chars[rng.gen_range(0..noise_chars.len())].to_string()
+        } else {
+            String::new()
+        };
         
         // Pick a random phosphor intensity
         let color = match rng.gen_range(0..10) {
@@ -249,7 +374,7 @@ pub fn phosphor_noise(
             stdout,
             cursor::MoveTo(noise_x, noise_y),
             SetForegroundColor(color),
-            //Print(noise_char),
+            Print(noise_char),
             ResetColor
         )?;
     }
@@ -260,11 +385,19 @@ pub fn phosphor_noise(
     Ok(())
 }
 
-// Simulate scan lines effect
-pub fn draw_scan_lines(phosphor_type: PhosphorType) -> Result<()> {
+// Simulate scan lines effect. `intensity` runs from 0.0 (invisible) to 1.0
+// (the dim phosphor colour).
+pub fn draw_scan_lines(phosphor_type: PhosphorType, intensity: f32) -> Result<()> {
     let (cols, rows) = terminal::size()?;
     let mut stdout = io::stdout();
     let (_, _, dim) = get_phosphor_colors(phosphor_type);
+    let dim = match dim {
+        Color::Rgb { r, g, b } => {
+            let scale = |c: u8| (c as f32 * intensity.clamp(0.0, 1.0)) as u8;
+            Color::Rgb { r: scale(r), g: scale(g), b: scale(b) }
+        }
+        other => other,
+    };
     
     // Save cursor position
     execute!(stdout, cursor::SavePosition)?;
@@ -0,0 +1,168 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{Error, ErrorKind, Result};
+use std::path::Path;
+use serde::Deserialize;
+use crate::story::{Step, Story};
+
+// The cues that ship with the game, used when no --cues file is given
+const BUNDLED_CUES: &str = include_str!("../sounds/cues.toml");
+
+// A library of named sound cues, e.g. [boot] or [fade]
+#[derive(Debug)]
+pub struct CueLibrary {
+    cues: BTreeMap<String, Cue>,
+}
+
+// A short tune for the PC speaker, played one row at a time like a tracker
+// pattern
+#[derive(Debug, Deserialize)]
+pub struct Cue {
+    #[serde(default)]
+    pub wave: Wave,
+    // For pulse waves, the fraction of each cycle the speaker is on
+    #[serde(default = "default_duty")]
+    pub duty: f32,
+    // Relative to the speaker's normal level
+    #[serde(default = "default_volume")]
+    pub volume: f32,
+    // Fade in and out of each note, in milliseconds
+    #[serde(default)]
+    pub attack: u64,
+    #[serde(default)]
+    pub release: u64,
+    // Each note is raised by up to this many Hz at random
+    #[serde(default)]
+    pub jitter: f32,
+    pub notes: Vec<Note>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Wave {
+    #[default]
+    Square,
+    Pulse,
+    Triangle,
+}
+
+fn default_duty() -> f32 {
+    0.5
+}
+
+fn default_volume() -> f32 {
+    1.0
+}
+
+// One row: a pitch and how long it lasts, written "800 150" (Hz and
+// milliseconds), "C#5 200" (a note name) or "--- 100" (a rest)
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Note {
+    // None for a rest
+    pub frequency: Option<f32>,
+    pub duration_ms: u64,
+}
+
+impl TryFrom<String> for Note {
+    type Error = String;
+
+    fn try_from(source: String) -> std::result::Result<Note, String> {
+        let mut fields = source.split_whitespace();
+        let (Some(pitch), Some(duration), None) = (fields.next(), fields.next(), fields.next()) else {
+            return Err(format!("'{}' should be a pitch and a duration, e.g. \"800 150\"", source));
+        };
+        let duration_ms = duration
+            .parse()
+            .map_err(|_| format!("'{}': '{}' is not a duration in milliseconds", source, duration))?;
+        let frequency = if pitch.chars().all(|c| c == '-') {
+            None
+        } else if pitch.starts_with(|c: char| c.is_ascii_digit()) {
+            let hz: f32 = pitch.parse().map_err(|_| format!("'{}': '{}' is not a frequency", source, pitch))?;
+            Some(hz)
+        } else {
+            Some(note_frequency(pitch).ok_or_else(|| format!("'{}': '{}' is not a note", source, pitch))?)
+        };
+        if frequency.is_some_and(|hz| hz <= 0.0) {
+            return Err(format!("'{}': frequency must be above zero", source));
+        }
+        Ok(Note { frequency, duration_ms })
+    }
+}
+
+// Equal temperament from A4 = 440 Hz, e.g. "C4", "F#3" or "Bb5"
+fn note_frequency(name: &str) -> Option<f32> {
+    let mut chars = name.chars();
+    let semitone = match chars.next()?.to_ascii_uppercase() {
+        'C' => 0,
+        'D' => 2,
+        'E' => 4,
+        'F' => 5,
+        'G' => 7,
+        'A' => 9,
+        'B' => 11,
+        _ => return None,
+    };
+    let rest = chars.as_str();
+    let (accidental, octave) = match rest.chars().next()? {
+        '#' => (1, &rest[1..]),
+        'b' => (-1, &rest[1..]),
+        _ => (0, rest),
+    };
+    let octave: i32 = octave.parse().ok()?;
+    let midi = 12 * (octave + 1) + semitone + accidental;
+    Some(440.0 * 2f32.powf((midi - 69) as f32 / 12.0))
+}
+
+impl CueLibrary {
+    pub fn bundled() -> Result<CueLibrary> {
+        CueLibrary::parse(BUNDLED_CUES)
+    }
+
+    pub fn load(path: &Path) -> Result<CueLibrary> {
+        let source = fs::read_to_string(path)?;
+        CueLibrary::parse(&source).map_err(|e| {
+            Error::new(e.kind(), format!("{}: {}", path.display(), e))
+        })
+    }
+
+    pub fn parse(source: &str) -> Result<CueLibrary> {
+        let cues: BTreeMap<String, Cue> = toml::from_str(source)
+            .map_err(|e| Error::new(ErrorKind::InvalidData, e.to_string()))?;
+        Ok(CueLibrary { cues })
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Cue> {
+        self.cues.get(name)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.cues.keys().map(String::as_str)
+    }
+
+    // Every cue the story plays, and every one in `required`, must be defined
+    pub fn check(&self, story: &Story, required: &[&str]) -> Result<()> {
+        for name in required {
+            if !self.cues.contains_key(*name) {
+                return Err(invalid(format!("sound cue '{}' is missing", name)));
+            }
+        }
+        for scene in &story.scenes {
+            for step in &scene.body {
+                if let Step::Sound { sound } = step {
+                    if !self.cues.contains_key(sound) {
+                        return Err(invalid(format!(
+                            "scene '{}' plays unknown sound cue '{}'",
+                            scene.id, sound
+                        )));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn invalid(message: String) -> Error {
+    Error::new(ErrorKind::InvalidData, message)
+}
@@ -1,41 +1,523 @@
 use std::io::{self, Result, Write};
+use std::sync::Mutex;
 use std::thread;
 use std::time::Duration;
 use colored::*;
+use unicode_width::UnicodeWidthStr;
 use rand::Rng;
+use chrono::{DateTime, Local};
+use crate::ambient;
+use crate::clock;
 use crate::sound; // Import the sound module
 use crate::crt_effects::{self, print_slowly_with_phosphor, PhosphorType}; // Import the CRT effects module
+use crate::render::{self, Fx, Input, Renderer};
+use crate::rng;
+use crate::story::Layer;
 use crossterm::{
     cursor,
-    event::{self, Event, KeyCode},
+    event::{
+        self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind, KeyModifiers,
+        MouseButton, MouseEventKind,
+    },
     execute,
-    style::{Color, Print, ResetColor, SetForegroundColor},
+    style::{Attribute, Color, Print, ResetColor, SetAttribute, SetForegroundColor},
     terminal::{self, Clear, ClearType},
 };
 
 // UI Helper functions
-pub fn get_choice() -> Result<i32> {
+
+// Choice menu: pick by number (as many digits as needed), by moving the
+// highlight with the arrow keys and pressing Enter, or by clicking a choice.
+// Only choices that are shown and enabled can be picked.
+pub fn menu(choices: &[(&str, bool)], hint: &str) -> Result<Input> {
+    let indent = 2; // Consistent with narrative text indentation
+    let mut stdout = io::stdout();
+
     // Drain any pending events in the queue before waiting for input
     while event::poll(Duration::from_millis(0))? {
         let _ = event::read()?;
     }
 
-    // Small delay to ensure terminal is ready for input
-    thread::sleep(Duration::from_millis(100));
+    print_choices(choices)?;
+    print_hint(hint)?;
+
+    // Typed digits are echoed on this row; the choices sit just above the hint,
+    // wherever scrolling has left them
+    let echo_row = cursor::position()?.1;
+    let first_row = echo_row.saturating_sub(2 + choices.len() as u16);
+    let row = |i: usize| first_row + i as u16;
+
+    let valid = |n: usize| n >= 1 && n <= choices.len() && choices[n - 1].1;
+    let mut selected = choices.iter().position(|(_, enabled)| *enabled);
+    let mut typed = String::new();
+    // Refused input is reported on the echo row until the next key
+    let mut refused = false;
+
+    let draw = |i: usize, highlighted: bool| -> Result<()> {
+        let (choice, enabled) = choices[i];
+        let color = if enabled { Color::DarkCyan } else { Color::DarkGrey };
+        let mut stdout = io::stdout();
+        execute!(stdout, cursor::MoveTo(indent + 2, row(i)), SetForegroundColor(color))?;
+        if highlighted {
+            execute!(stdout, SetAttribute(Attribute::Reverse))?;
+        }
+        execute!(stdout, Print(choice), SetAttribute(Attribute::Reset), ResetColor)
+    };
+    if let Some(i) = selected {
+        draw(i, true)?;
+    }
+
+    execute!(stdout, EnableMouseCapture)?;
+    let input = loop {
+        let previous = selected;
+        let Some(event) = next_event()? else {
+            break Input::Expired;
+        };
+        let key_pressed = matches!(event, Event::Key(_));
+        match event {
+            Event::Key(key) if key.kind == KeyEventKind::Press => {
+                let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+                match key.code {
+                    // Raw mode turns Ctrl-C into a key press rather than a signal
+                    KeyCode::Char('c') if ctrl => break Input::Disconnect,
+                    KeyCode::Char(c) if c.eq_ignore_ascii_case(&'s') => break Input::Save,
+                    KeyCode::Char(c) if c.eq_ignore_ascii_case(&'l') => break Input::Load,
+                    KeyCode::Char(c) if c.is_ascii_digit() => {
+                        typed.push(c);
+                        let n: usize = typed.parse().unwrap_or(0);
+                        if valid(n) {
+                            selected = Some(n - 1);
+                            // Pick at once unless another digit could still follow
+                            if n * 10 > choices.len() {
+                                break Input::Choice(n as i32);
+                            }
+                        } else if n * 10 > choices.len() {
+                            refused = true;
+                        }
+                    }
+                    KeyCode::Backspace => {
+                        typed.pop();
+                    }
+                    KeyCode::Esc => typed.clear(),
+                    KeyCode::Up => {
+                        typed.clear();
+                        if let Some(i) = selected {
+                            selected = (0..i).rev().find(|&j| choices[j].1).or(selected);
+                        }
+                    }
+                    KeyCode::Down => {
+                        typed.clear();
+                        if let Some(i) = selected {
+                            selected = (i + 1..choices.len()).find(|&j| choices[j].1).or(selected);
+                        }
+                    }
+                    KeyCode::Enter => {
+                        let n = match typed.as_str() {
+                            "" => selected.map_or(0, |i| i + 1),
+                            typed => typed.parse().unwrap_or(0),
+                        };
+                        if valid(n) {
+                            break Input::Choice(n as i32);
+                        }
+                        refused = true;
+                    }
+                    _ => {}
+                }
+            }
+            Event::Mouse(mouse) if mouse.kind == MouseEventKind::Down(MouseButton::Left) => {
+                if let Some(i) = (0..choices.len()).find(|&i| row(i) == mouse.row) {
+                    if valid(i + 1) {
+                        break Input::Choice(i as i32 + 1);
+                    }
+                    refused = true;
+                }
+            }
+            _ => {}
+        }
+
+        if selected != previous {
+            if let Some(i) = previous {
+                draw(i, false)?;
+            }
+            if let Some(i) = selected {
+                draw(i, true)?;
+            }
+        }
+        if refused {
+            refused = false;
+            typed.clear();
+            sound::error_sound()?;
+            execute!(
+                stdout,
+                cursor::MoveTo(indent + 2, echo_row),
+                SetForegroundColor(Color::Red),
+                Print(render::INVALID_INPUT),
+                Clear(ClearType::UntilNewLine),
+                ResetColor
+            )?;
+        } else if key_pressed {
+            execute!(
+                stdout,
+                cursor::MoveTo(indent + 2, echo_row),
+                SetForegroundColor(Color::Cyan),
+                Print(if typed.is_empty() { String::new() } else { format!("> {}", typed) }),
+                Clear(ClearType::UntilNewLine),
+                ResetColor
+            )?;
+        }
+        stdout.flush()?;
+    };
+    execute!(stdout, DisableMouseCapture, cursor::MoveTo(0, echo_row))?;
+
+    Ok(input)
+}
+
+// Raw-mode input line for typed commands: cursor movement, backspace, Ctrl-W,
+// up/down history and tab completion, in phosphor colours
+pub struct LineEditor {
+    history: Vec<String>,
+    phosphor: PhosphorType,
+}
+
+impl LineEditor {
+    pub fn new(phosphor: PhosphorType) -> LineEditor {
+        LineEditor { history: Vec::new(), phosphor }
+    }
+
+    // Read one line; `verbs` are offered for completion of the first word
+    pub fn read_line(&mut self, verbs: &[String]) -> Result<Input> {
+        let indent = 2; // Consistent with narrative text indentation
+        let prompt = "> ";
+        let (bright, medium, _) = crt_effects::get_phosphor_colors(self.phosphor);
+        let mut stdout = io::stdout();
+
+        // Drain any pending events in the queue before waiting for input
+        while event::poll(Duration::from_millis(0))? {
+            let _ = event::read()?;
+        }
+
+        println!();
+        execute!(
+            stdout,
+            cursor::MoveToColumn(indent),
+            SetForegroundColor(medium),
+            Print(prompt),
+            cursor::SetCursorStyle::BlinkingBlock,
+            cursor::Show
+        )?;
+        stdout.flush()?;
+
+        let start = indent + prompt.width() as u16;
+        let mut line: Vec<char> = Vec::new();
+        let mut pos = 0;
+        // Index into history while browsing it, and the line being typed before
+        let mut browsing: Option<usize> = None;
+        let mut draft: Vec<char> = Vec::new();
+
+        let input = loop {
+            let Some(event) = next_event()? else {
+                break Input::Expired;
+            };
+            let Event::Key(key) = event else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+            match key.code {
+                KeyCode::Char('c') if ctrl => break Input::Disconnect,
+                KeyCode::Enter => {
+                    let text: String = line.iter().collect();
+                    if !text.trim().is_empty() && self.history.last() != Some(&text) {
+                        self.history.push(text.clone());
+                    }
+                    break Input::Text(text);
+                }
+                KeyCode::Left if pos > 0 => pos -= 1,
+                KeyCode::Right if pos < line.len() => pos += 1,
+                KeyCode::Home => pos = 0,
+                KeyCode::Char('a') if ctrl => pos = 0,
+                KeyCode::End => pos = line.len(),
+                KeyCode::Char('e') if ctrl => pos = line.len(),
+                KeyCode::Backspace if pos > 0 => {
+                    pos -= 1;
+                    line.remove(pos);
+                }
+                KeyCode::Delete if pos < line.len() => {
+                    line.remove(pos);
+                }
+                // Delete the word before the cursor, and any spaces after it
+                KeyCode::Char('w') if ctrl => {
+                    let mut from = pos;
+                    while from > 0 && line[from - 1] == ' ' {
+                        from -= 1;
+                    }
+                    while from > 0 && line[from - 1] != ' ' {
+                        from -= 1;
+                    }
+                    line.drain(from..pos);
+                    pos = from;
+                }
+                KeyCode::Up if !self.history.is_empty() => {
+                    let i = match browsing {
+                        None => {
+                            draft = line.clone();
+                            self.history.len() - 1
+                        }
+                        Some(i) => i.saturating_sub(1),
+                    };
+                    browsing = Some(i);
+                    line = self.history[i].chars().collect();
+                    pos = line.len();
+                }
+                KeyCode::Down => {
+                    if let Some(i) = browsing {
+                        if i + 1 < self.history.len() {
+                            browsing = Some(i + 1);
+                            line = self.history[i + 1].chars().collect();
+                        } else {
+                            browsing = None;
+                            line = std::mem::take(&mut draft);
+                        }
+                        pos = line.len();
+                    }
+                }
+                KeyCode::Tab => {
+                    if let Some(completed) = complete(&line, pos, verbs) {
+                        line = completed;
+                        pos = line.len();
+                    }
+                }
+                KeyCode::Char(c) if !ctrl => {
+                    line.insert(pos, c);
+                    pos += 1;
+                }
+                _ => continue,
+            }
+
+            // Redraw the line and put the cursor back where it belongs
+            let text: String = line.iter().collect();
+            let before: String = line[..pos].iter().collect();
+            execute!(
+                stdout,
+                cursor::MoveToColumn(start),
+                SetForegroundColor(bright),
+                Print(&text),
+                terminal::Clear(ClearType::UntilNewLine),
+                cursor::MoveToColumn(start + before.width() as u16)
+            )?;
+            stdout.flush()?;
+        };
+
+        execute!(stdout, ResetColor, cursor::Hide, cursor::SetCursorStyle::DefaultUserShape)?;
+        println!();
+        execute!(stdout, cursor::MoveToColumn(0))?;
+        Ok(input)
+    }
+}
+
+// Complete the first word from the known verbs, as far as they agree.
+// Only applies while the cursor is at the end of that word.
+fn complete(line: &[char], pos: usize, verbs: &[String]) -> Option<Vec<char>> {
+    let typed: String = line.iter().collect();
+    if pos != line.len() || typed.trim_start().contains(' ') {
+        return None;
+    }
+    let word = typed.trim_start().to_lowercase();
+    let matching: Vec<&String> = verbs.iter().filter(|v| v.starts_with(&word)).collect();
+    let first = matching.first()?;
+    let mut common = first.len();
+    for verb in &matching[1..] {
+        common = first
+            .chars()
+            .zip(verb.chars())
+            .take_while(|(a, b)| a == b)
+            .count()
+            .min(common);
+    }
+    let mut completed: String = first.chars().take(common).collect();
+    // A single match is a whole word, ready for the next one
+    if matching.len() == 1 {
+        completed.push(' ');
+    }
+    (completed.len() > word.len()).then(|| completed.chars().collect())
+}
+
+// Ask for a save slot; None if the player presses Esc. Ctrl-C disconnects,
+// as at any other choice.
+pub fn get_slot(action: &str, slots: u8) -> Result<Option<u8>> {
+    let indent = 2; // Consistent with narrative text indentation
+    let mut stdout = io::stdout();
+
+    println!();
+    execute!(stdout, cursor::MoveToColumn(indent))?;
+    print_message(&format!("{} WHICH SLOT? (1-{}, ESC TO CANCEL)", action, slots), Color::Cyan)?;
+    println!();
 
-    // Now wait for a valid key press
     loop {
-        if let Event::Key(key_event) = event::read()? {
-            if let KeyCode::Char(c) = key_event.code {
-                if c.is_digit(10) {
-                    let num = c.to_digit(10).unwrap() as i32;
-                    if num > 0 {  // Only return digits 1-9, not 0
-                        return Ok(num);
+        let Some(event) = next_event()? else {
+            return Ok(None);
+        };
+        let Event::Key(key) = event else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Char('c') if ctrl => return Err(render::disconnected()),
+            KeyCode::Esc => return Ok(None),
+            KeyCode::Char(c) => {
+                if let Some(slot) = c.to_digit(10) {
+                    if slot >= 1 && slot <= slots as u32 {
+                        return Ok(Some(slot as u8));
                     }
                 }
             }
+            _ => {}
+        }
+    }
+}
+
+// Wait for the next terminal event, keeping the status bar's clocks live.
+// None if the countdown runs out first.
+fn next_event() -> Result<Option<Event>> {
+    loop {
+        if event::poll(Duration::from_millis(250))? {
+            return event::read().map(Some);
+        }
+        if clock::expired() {
+            return Ok(None);
+        }
+        print_status_bar()?;
+    }
+}
+
+// Status bar on the bottom row: hours left, the scene, the link and the
+// system time. It lives in a static so the input loops can keep it live while
+// they wait, as they do the countdown.
+struct Status {
+    scene: String,
+    hours: i32,
+}
+
+static STATUS: Mutex<Option<Status>> = Mutex::new(None);
+
+pub fn set_status(scene: &str, hours: i32) -> Result<()> {
+    *STATUS.lock().unwrap() = Some(Status { scene: scene.to_string(), hours });
+    print_status_bar()
+}
+
+pub fn set_status_hours(hours: i32) -> Result<()> {
+    if let Some(status) = STATUS.lock().unwrap().as_mut() {
+        status.hours = hours;
+    }
+    print_status_bar()
+}
+
+// Take the bar down and give its row back to the text, e.g. for the ending screen
+pub fn hide_status() -> Result<()> {
+    if STATUS.lock().unwrap().take().is_none() {
+        return Ok(());
+    }
+    let (_, rows) = terminal::size()?;
+    execute!(
+        io::stdout(),
+        cursor::SavePosition,
+        Print("\x1b[r"),
+        cursor::MoveTo(0, rows.saturating_sub(1)),
+        Clear(ClearType::CurrentLine),
+        cursor::RestorePosition
+    )
+}
+
+// Four bars of signal while there's time to spare, dropping with the same
+// thresholds as the hours' colours
+fn link_bars(hours: i32) -> usize {
+    match hours {
+        h if h > 6 => 4,
+        h if h > 3 => 3,
+        h if h > 1 => 2,
+        h if h > 0 => 1,
+        _ => 0,
+    }
+}
+
+pub fn print_status_bar() -> Result<()> {
+    let status = STATUS.lock().unwrap();
+    let Some(status) = status.as_ref() else {
+        return Ok(());
+    };
+    let (cols, rows) = terminal::size()?;
+    if rows < 2 {
+        return Ok(());
+    }
+    let (bright, medium, dim) = crt_effects::get_phosphor_colors(PhosphorType::Green);
+
+    // In countdown mode the wall clock is spending hours between scenes
+    let hours = clock::hours_left().unwrap_or(status.hours);
+    let hours_color = if hours <= 3 {
+        Color::Red
+    } else if hours <= 6 {
+        Color::Yellow
+    } else {
+        Color::Green
+    };
+
+    // A weak link can't hold steady: the label flickers once a second
+    let bars = link_bars(hours);
+    let now = Local::now();
+    let link_color = if bars <= 1 && now.timestamp() % 2 == 0 { dim } else { medium };
+    let signal: String = "▂▄▆█".chars().take(bars).collect();
+    let lost: String = "····".chars().skip(bars).collect();
+
+    let mut segments = vec![
+        (format!(" {} HOURS ", hours), hours_color),
+        ("│".to_string(), dim),
+        (" LINK ".to_string(), link_color),
+        (signal, bright),
+        (lost, dim),
+        (" │".to_string(), dim),
+        (format!(" {} ", status.scene.to_uppercase()), medium),
+    ];
+    if let Some(secs) = clock::game_seconds_left() {
+        segments.push(("│".to_string(), dim));
+        segments.push((
+            format!(" EXPIRES {:02}:{:02}:{:02} ", secs / 3600, secs / 60 % 60, secs % 60),
+            hours_color,
+        ));
+    }
+    let time = format!(" {} ", now.format("%a %b %e %T %Y"));
+
+    let mut stdout = io::stdout();
+    // Keep scrolling text off the bottom row. Setting the region homes the
+    // cursor, so it goes inside the save and restore.
+    execute!(
+        stdout,
+        cursor::SavePosition,
+        Print(format!("\x1b[1;{}r", rows - 1)),
+        cursor::MoveTo(0, rows - 1),
+        Clear(ClearType::CurrentLine)
+    )?;
+    let mut width = 0;
+    for (text, color) in &segments {
+        width += text.width();
+        if width > cols as usize {
+            break;
         }
+        execute!(stdout, SetForegroundColor(*color), Print(text))?;
+    }
+    if width + time.width() <= cols as usize {
+        execute!(
+            stdout,
+            cursor::MoveTo(cols - time.width() as u16, rows - 1),
+            SetForegroundColor(bright),
+            Print(&time)
+        )?;
     }
+    execute!(stdout, ResetColor, cursor::RestorePosition)
 }
 
 pub fn clear_screen() -> Result<()> {
@@ -51,7 +533,7 @@ pub fn print_title() {
     let mut stdout = io::stdout();
 
     // Apply scan lines effect for CRT look
-    crt_effects::draw_scan_lines(PhosphorType::Green).unwrap_or(());
+    crt_effects::draw_scan_lines(PhosphorType::Green, 1.0).unwrap_or(());
 
     // First show the logo (SYN-TEC ASCII art)
     println!(); // Extra spacing at the top
@@ -71,10 +553,10 @@ pub fn print_title() {
         println!("{}", "   ╚══════╝    ╚═╝    ╚═╝  ╚═══╝          ╚═╝    ╚══════╝  ╚═════╝ ".bright_blue());
 
         // Add random phosphor noise around the logo for authentic CRT look
-        let mut rng = rand::thread_rng();
+        let mut rng = rng::game_rng();
         if rng.gen_bool(0.2) { // 80% chance of noise
             let logo_y_position = 1; // Approximate line where logo starts
-            crt_effects::phosphor_noise(indent as u16, logo_y_position, 80, 6, PhosphorType::Blue, 0.03).unwrap_or(());
+            crt_effects::phosphor_noise(indent, logo_y_position, 80, 6, PhosphorType::Blue, 0.03).unwrap_or(());
         }
     }
 
@@ -87,7 +569,7 @@ pub fn print_title() {
     };
 
     let init_text = "Initialising...";
-    crt_effects::print_with_phosphor(init_text, indent as u16, y_pos, PhosphorType::Green, 50).unwrap_or(());
+    crt_effects::print_with_phosphor(init_text, indent, y_pos, PhosphorType::Green, 50).unwrap_or(());
 
     // No divider here as per requested sequence
     println!(); // Just add spacing after initialization text
@@ -160,7 +642,7 @@ pub fn print_narrative_with_phosphor(text: &str, phosphor_type: PhosphorType) ->
                 // Check if adding this word would exceed the max length
                 if current_line.len() + word.len() + 1 > max_line_length && !current_line.is_empty() {
                     // Use phosphor effect for the current line
-                    print_slowly_with_phosphor(&current_line, indent as u16, y_position, phosphor_type, 15)?;
+                    print_slowly_with_phosphor(&current_line, indent, y_position, phosphor_type, 15)?;
 
                     // Move to next line with additional indent for wrapped lines
                     y_position += 1;
@@ -176,12 +658,12 @@ pub fn print_narrative_with_phosphor(text: &str, phosphor_type: PhosphorType) ->
 
             // Print any remaining text
             if !current_line.is_empty() {
-                print_slowly_with_phosphor(&current_line, indent as u16, y_position, phosphor_type, 15)?;
+                print_slowly_with_phosphor(&current_line, indent, y_position, phosphor_type, 15)?;
                 y_position += 1;
             }
         } else {
             // For lines that don't need wrapping, use phosphor effect directly
-            print_slowly_with_phosphor(line, indent as u16, y_position, phosphor_type, 15)?;
+            print_slowly_with_phosphor(line, indent, y_position, phosphor_type, 15)?;
             y_position += 1;
         }
     }
@@ -189,36 +671,25 @@ pub fn print_narrative_with_phosphor(text: &str, phosphor_type: PhosphorType) ->
     // Ensure cursor is positioned correctly after all text
     execute!(io::stdout(), cursor::MoveTo(0, y_position))?;
 
-    // Add random phosphor noise effect (subtle static) after the text
-    let mut rng = rand::thread_rng();
-    if rng.gen_bool(0.3) { // 30% chance of noise
+    // Add random phosphor noise effect (subtle static) after the text: a 30%
+    // chance at first, growing heavier and more likely as the display decays
+    let mut rng = rng::game_rng();
+    let decay = crt_effects::decay();
+    if rng.gen_bool(0.3 + 0.6 * decay) {
         let line_count = text.lines().count();
-        crt_effects::phosphor_noise(indent as u16, y_position.saturating_sub(line_count as u16),
-                                term_width as u16 - (indent as u16 * 2), line_count as u16,
-                                phosphor_type, 0.05)?;
+        crt_effects::phosphor_noise(indent, y_position.saturating_sub(line_count as u16),
+                                term_width as u16 - (indent * 2), line_count as u16,
+                                phosphor_type, 0.05 + 0.15 * decay as f32)?;
     }
 
-    thread::sleep(Duration::from_millis(500));
-    Ok(())
-}
-
-pub fn print_hours(hours: i32) -> Result<()> {
-    let hours_text = format!("{} hours now remain.", hours);
-    let indent = 2; // Consistent with narrative text indentation
-
-    let mut stdout = io::stdout();
-    execute!(stdout, cursor::MoveToColumn(indent))?;
-
-    if hours <= 3 {
-        print_message(&hours_text, Color::Red)
-    } else if hours <= 6 {
-        print_message(&hours_text, Color::Yellow)
-    } else {
-        print_message(&hours_text, Color::Green)
+    if !crt_effects::skipping()? {
+        crt_effects::type_delay(500);
     }
+    Ok(())
 }
 
-pub fn print_choices(choices: &[&str]) -> Result<()> {
+// Each choice is shown with whether it can be picked; unavailable ones are greyed out
+pub fn print_choices(choices: &[(&str, bool)]) -> Result<()> {
     let indent = 2; // Consistent with narrative text indentation
     let mut stdout = io::stdout();
 
@@ -229,16 +700,27 @@ pub fn print_choices(choices: &[&str]) -> Result<()> {
     print_message("What next?", Color::Cyan)?;
 
     // Print each choice with consistent indentation
-    for choice in choices {
+    for (choice, enabled) in choices {
         println!();
         execute!(stdout, cursor::MoveToColumn(indent + 2))?; // Additional indent for choices
-        print_message(choice, Color::DarkCyan)?;
+        print_message(choice, if *enabled { Color::DarkCyan } else { Color::DarkGrey })?;
     }
 
     println!();
     Ok(())
 }
 
+// Dim line listing the extra keys available at a prompt
+pub fn print_hint(hint: &str) -> Result<()> {
+    let indent = 2; // Consistent with narrative text indentation
+
+    println!();
+    execute!(io::stdout(), cursor::MoveToColumn(indent + 2))?;
+    print_message(hint, Color::DarkGrey)?;
+    println!();
+    Ok(())
+}
+
 pub fn print_divider() -> Result<()> {
     println!(); // Space before divider
     let mut stdout = io::stdout();
@@ -260,6 +742,19 @@ pub fn print_message(message: &str, color: Color) -> Result<()> {
     )
 }
 
+// A one-line system notice, such as a save confirmation
+pub fn print_notice(message: &str) -> Result<()> {
+    let indent = 2; // Consistent with narrative text indentation
+    let mut stdout = io::stdout();
+
+    sound::beep()?;
+
+    execute!(stdout, cursor::MoveToColumn(indent))?;
+    print_message(message, Color::Green)?;
+    println!();
+    Ok(())
+}
+
 pub fn print_error(message: &str) -> Result<()> {
     let indent = 2; // Consistent with narrative text indentation
     let mut stdout = io::stdout();
@@ -338,7 +833,9 @@ pub fn print_epilogue(text: &str) -> Result<()> {
             )?;
 
             stdout.flush()?;
-            thread::sleep(Duration::from_millis(30));
+            if !crt_effects::skipping()? {
+                crt_effects::type_delay(30);
+            }
         }
 
         // New line after each line of text
@@ -355,6 +852,29 @@ pub fn print_epilogue(text: &str) -> Result<()> {
     Ok(())
 }
 
+// Final prompt before the game closes
+pub fn print_exit_prompt() -> Result<()> {
+    print_message(" Press any key to exit...", Color::White)?;
+    wait_for_key()
+}
+
+// Shutdown sequence when the player cuts the link with Ctrl-C
+pub fn print_disconnect() -> Result<()> {
+    let indent = 2; // Consistent with narrative text indentation
+    let mut stdout = io::stdout();
+
+    println!();
+    sound::error_sound()?;
+    execute!(stdout, cursor::MoveToColumn(indent))?;
+    print_message("REMOTE LINK SEVERED BY USER.", Color::Red)?;
+    println!();
+    execute!(stdout, cursor::MoveToColumn(indent))?;
+    print_message("SUBJECT UNATTENDED. POWERING DOWN TERMINAL...", Color::DarkGrey)?;
+    sound::fade_sound()?;
+    thread::sleep(Duration::from_millis(1000));
+    clear_screen()
+}
+
 pub fn wait_for_key() -> Result<()> {
     loop {
         if let Event::Key(_) = event::read()? {
@@ -430,7 +950,7 @@ pub fn print_ending_screen() -> Result<()> {
 // Lighter flicker that just flashes specific characters
 pub fn light_flicker() -> Result<()> {
     let mut stdout = io::stdout();
-    let mut rng = rand::thread_rng();
+    let mut rng = rng::game_rng();
     let (cols, rows) = terminal::size()?;
 
     // Play flicker sound with 80% probability (so not every visual flicker has sound)
@@ -471,10 +991,10 @@ pub fn light_flicker() -> Result<()> {
 
 // Main function that randomly decides whether to flicker
 pub fn random_flicker_check() -> Result<()> {
-    let mut rng = rand::thread_rng();
+    let mut rng = rng::game_rng();
 
-    // 5% chance of a flicker effect occurring
-    if rng.gen_bool(0.05) {
+    // 5% chance of a flicker effect occurring, up to 40% as the display decays
+    if rng.gen_bool(0.05 + 0.35 * crt_effects::decay()) {
         // Only use the light flicker effect
         light_flicker()?;
 
@@ -483,4 +1003,156 @@ pub fn random_flicker_check() -> Result<()> {
     }
 
     Ok(())
-}
\ No newline at end of file
+}
+
+// The full CRT terminal experience: phosphor text, flickers and PC speaker sounds
+pub struct CrtRenderer {
+    editor: LineEditor,
+}
+
+impl CrtRenderer {
+    pub fn new() -> CrtRenderer {
+        CrtRenderer { editor: LineEditor::new(PhosphorType::Green) }
+    }
+}
+
+impl Renderer for CrtRenderer {
+    fn power_on(&mut self) -> Result<()> {
+        clear_screen()?;
+
+        // Classic PC XT boot sound with CRT power-on effect
+        sound::boot_sound()?;
+        crt_effects::crt_power_on(PhosphorType::Green)
+    }
+
+    fn boot(&mut self, started: &DateTime<Local>) -> Result<()> {
+        print_title();
+
+        thread::sleep(Duration::from_millis(1000));
+
+        // Welcome message with phosphor persistence effect; any key skips the
+        // rest of the sequence
+        crt_effects::begin_passage();
+        print_slowly_with_phosphor("WELCOME, USER. CURRENT SYSTEM TIME: ", 2, 11, PhosphorType::Green, 30)?;
+
+        let time_str = format!("{}", started.format("%a %b %e %T %Y"));
+        print_slowly_with_phosphor(&time_str, 2, 12, PhosphorType::Blue, 30)?;
+
+        // Modem-like connection sound for establishing link
+        print_slowly_with_phosphor("ESTABLISHING REMOTE LINK...", 2, 14, PhosphorType::Green, 30)?;
+        sound::connection_sound()?;
+        print_divider()?;
+
+        // Print divider after connection messages
+        println!("\n");
+        execute!(io::stdout(), cursor::MoveToColumn(2))?;
+
+        // Success beep when connection is complete (after divider)
+        sound::beep()?;
+        print_slowly_with_phosphor("BEGIN.", 2, 18, PhosphorType::Amber, 50)?;
+
+        thread::sleep(Duration::from_millis(2000));
+        Ok(())
+    }
+
+    // A scene's text is typed out as one passage from its clear to its
+    // prompt, so a single key finishes all of it
+    fn clear(&mut self) -> Result<()> {
+        crt_effects::begin_passage();
+        clear_screen()?;
+        // Scan lines show through more strongly as the display decays
+        let decay = crt_effects::decay();
+        if decay > 0.0 {
+            crt_effects::draw_scan_lines(PhosphorType::Green, decay as f32)?;
+        }
+        print_status_bar()
+    }
+
+    fn divider(&mut self) -> Result<()> {
+        print_divider()
+    }
+
+    fn narrative(&mut self, text: &str) -> Result<()> {
+        print_narrative(text)
+    }
+
+    // The hours stay on the status bar rather than in the text
+    fn hours(&mut self, hours: i32) -> Result<()> {
+        set_status_hours(hours)
+    }
+
+    fn status(&mut self, scene: &str, hours: i32, decay: f64) -> Result<()> {
+        crt_effects::set_decay(decay);
+        set_status(scene, hours)
+    }
+
+    fn choices(&mut self, choices: &[(&str, bool)]) -> Result<()> {
+        print_choices(choices)
+    }
+
+    fn hint(&mut self, hint: &str) -> Result<()> {
+        print_hint(hint)
+    }
+
+    fn notice(&mut self, message: &str) -> Result<()> {
+        print_notice(message)
+    }
+
+    fn error(&mut self, message: &str) -> Result<()> {
+        print_error(message)
+    }
+
+    fn epilogue(&mut self, text: &str) -> Result<()> {
+        print_epilogue(text)
+    }
+
+    fn effect(&mut self, fx: Fx<'_>) -> Result<()> {
+        match fx {
+            // No need to redraw the screen or reprint text, as light_flicker is subtle
+            Fx::Flicker => light_flicker(),
+            Fx::FlickerCheck => random_flicker_check(),
+            Fx::Sound(cue) => sound::play(cue),
+            Fx::Pause(ms) => {
+                thread::sleep(Duration::from_millis(ms));
+                Ok(())
+            }
+        }
+    }
+
+    fn ambience(&mut self, layers: &[Layer]) -> Result<()> {
+        ambient::set(layers);
+        Ok(())
+    }
+
+    fn ending_screen(&mut self) -> Result<()> {
+        hide_status()?;
+        print_ending_screen()
+    }
+
+    fn disconnect(&mut self) -> Result<()> {
+        hide_status()?;
+        print_disconnect()
+    }
+
+    fn exit_prompt(&mut self) -> Result<()> {
+        print_exit_prompt()
+    }
+
+    // Whatever follows an answer, e.g. a scene replayed with "look", is
+    // typed out afresh
+    fn menu(&mut self, choices: &[(&str, bool)], hint: &str) -> Result<Input> {
+        let input = menu(choices, hint);
+        crt_effects::begin_passage();
+        input
+    }
+
+    fn command(&mut self, verbs: &[String]) -> Result<Input> {
+        let input = self.editor.read_line(verbs);
+        crt_effects::begin_passage();
+        input
+    }
+
+    fn slot(&mut self, action: &str, slots: u8) -> Result<Option<u8>> {
+        get_slot(action, slots)
+    }
+}
@@ -0,0 +1,193 @@
+use std::fmt::Write;
+
+use crate::state::{Effect, Op};
+use crate::story::{Choice, Scene, Story, HOURS};
+
+// Graphviz DOT: choices are edges, endings are double octagons
+pub fn dot(story: &Story) -> String {
+    let mut out = String::new();
+    writeln!(out, "digraph story {{").unwrap();
+    writeln!(out, "    node [shape=box, fontname=\"monospace\"];").unwrap();
+    writeln!(out, "    edge [fontname=\"monospace\", fontsize=10];").unwrap();
+    for scene in &story.scenes {
+        let mut attrs = vec![format!("label=\"{}\"", scene_label(scene, "\\n", escape_dot))];
+        if scene.is_ending() {
+            attrs.push("shape=doubleoctagon, style=filled, fillcolor=\"#f4cccc\"".to_string());
+        }
+        if scene.id == story.start {
+            attrs.push("penwidth=2".to_string());
+        }
+        writeln!(out, "    \"{}\" [{}];", escape_dot(&scene.id), attrs.join(", ")).unwrap();
+    }
+    for scene in &story.scenes {
+        for choice in &scene.choices {
+            writeln!(
+                out,
+                "    \"{}\" -> \"{}\" [label=\"{}\"];",
+                escape_dot(&scene.id),
+                escape_dot(&choice.goto),
+                escape_dot(&choice_label(story, choice))
+            )
+            .unwrap();
+        }
+    }
+    writeln!(out, "}}").unwrap();
+    out
+}
+
+// Mermaid flowchart: endings are stadium-shaped and use the `ending` class
+pub fn mermaid(story: &Story) -> String {
+    let mut out = String::new();
+    writeln!(out, "flowchart TD").unwrap();
+    for scene in &story.scenes {
+        let label = scene_label(scene, "<br/>", escape_mermaid);
+        if scene.is_ending() {
+            writeln!(out, "    {}([\"{}\"]):::ending", node_id(scene), label).unwrap();
+        } else {
+            writeln!(out, "    {}[\"{}\"]", node_id(scene), label).unwrap();
+        }
+    }
+    for scene in &story.scenes {
+        for choice in &scene.choices {
+            let target = story.scene(&choice.goto).expect("choice targets are checked on load");
+            writeln!(
+                out,
+                "    {} -->|\"{}\"| {}",
+                node_id(scene),
+                escape_mermaid(&choice_label(story, choice)),
+                node_id(target)
+            )
+            .unwrap();
+        }
+    }
+    writeln!(out, "    classDef ending fill:#f4cccc,stroke:#990000,stroke-width:2px").unwrap();
+    out
+}
+
+fn scene_label(scene: &Scene, newline: &str, escape: fn(&str) -> String) -> String {
+    match scene.ending {
+        Some(n) => format!("{}{}ENDING {}", escape(&scene.id), newline, n),
+        None => escape(&scene.id),
+    }
+}
+
+// Choice text and what taking it does to the hours, counting the effects of
+// the choice and of entering the scene it leads to
+fn choice_label(story: &Story, choice: &Choice) -> String {
+    let target = story.scene(&choice.goto).expect("choice targets are checked on load");
+    match hour_cost(choice.set.iter().chain(&target.enter)) {
+        Some(cost) => format!("{} ({})", choice.label, cost),
+        None => choice.label.clone(),
+    }
+}
+
+fn hour_cost<'a>(effects: impl Iterator<Item = &'a Effect>) -> Option<String> {
+    let mut change = 0;
+    let mut set = None;
+    for effect in effects.filter(|e| e.var == HOURS) {
+        match &effect.op {
+            Op::Set(value) => {
+                set = Some(value.to_string());
+                change = 0;
+            }
+            Op::Add(n) => change += n,
+        }
+    }
+    match (set, change) {
+        (None, 0) => None,
+        (None, n) => Some(format!("{:+}h", n)),
+        (Some(value), 0) => Some(format!("{} = {}", HOURS, value)),
+        (Some(value), n) => Some(format!("{} = {} {:+}", HOURS, value, n)),
+    }
+}
+
+// Mermaid ids can't contain most punctuation, and "end" is a keyword
+fn node_id(scene: &Scene) -> String {
+    let id: String = scene
+        .id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' { c } else { '_' })
+        .collect();
+    if id.eq_ignore_ascii_case("end") { format!("{}_", id) } else { id }
+}
+
+fn escape_dot(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn escape_mermaid(text: &str) -> String {
+    text.replace('"', "#quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A quoted label, every kind of hour cost, and an ending named "end",
+    // which Mermaid reserves
+    const STORY: &str = r#"
+        start = "start"
+        [variables]
+        hours = 5
+        [[scenes]]
+        id = "start"
+        choices = [
+            { label = 'Say "hi".', goto = "end", set = ["hours -= 1"] },
+            { label = "Wait.", goto = "room" },
+        ]
+        [[scenes]]
+        id = "room"
+        enter = ["hours = 2"]
+        choices = [
+            { label = "Leave.", goto = "end", set = ["hours = 6"] },
+            { label = "Look around.", goto = "room", set = ["hours += 0"] },
+        ]
+        [[scenes]]
+        id = "end"
+        enter = ["hours -= 2"]
+        ending = 3
+    "#;
+
+    #[test]
+    fn dot_draws_scenes_choices_and_endings() {
+        let story = Story::parse(STORY).unwrap();
+        assert_eq!(
+            dot(&story),
+            r##"digraph story {
+    node [shape=box, fontname="monospace"];
+    edge [fontname="monospace", fontsize=10];
+    "start" [label="start", penwidth=2];
+    "room" [label="room"];
+    "end" [label="end\nENDING 3", shape=doubleoctagon, style=filled, fillcolor="#f4cccc"];
+    "start" -> "end" [label="Say \"hi\". (-3h)"];
+    "start" -> "room" [label="Wait. (hours = 2)"];
+    "room" -> "end" [label="Leave. (hours = 6 -2)"];
+    "room" -> "room" [label="Look around. (hours = 2)"];
+}
+"##
+        );
+    }
+
+    #[test]
+    fn mermaid_draws_scenes_choices_and_endings() {
+        let story = Story::parse(STORY).unwrap();
+        assert_eq!(
+            mermaid(&story),
+            r#"flowchart TD
+    start["start"]
+    room["room"]
+    end_(["end<br/>ENDING 3"]):::ending
+    start -->|"Say #quot;hi#quot;. (-3h)"| end_
+    start -->|"Wait. (hours = 2)"| room
+    room -->|"Leave. (hours = 6 -2)"| end_
+    room -->|"Look around. (hours = 2)"| room
+    classDef ending fill:#f4cccc,stroke:#990000,stroke-width:2px
+"#
+        );
+    }
+
+    #[test]
+    fn dot_escapes_backslashes() {
+        assert_eq!(escape_dot(r#"a\b "c""#), r#"a\\b \"c\""#);
+    }
+}
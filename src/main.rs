@@ -1,92 +1,318 @@
-use std::io::{self, Result};
-use std::thread;
+use std::env;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, ErrorKind, Result};
+use std::path::PathBuf;
+use std::process;
 use std::time::Duration;
 use chrono::Local;
 // use colored::{Color, *};
-use crossterm::{
-    cursor,
-    execute,
-    style::{ResetColor},
-    terminal::{self, Clear, ClearType},
-};
 
 // Include project modules
+mod ambient;
+mod audio;
+mod clock;
 mod display;
+mod graph;
 mod narrative;
+mod parser;
 mod sound;
+mod synth;
 mod crt_effects;
+mod condition;
+mod cue;
+mod render;
+mod state;
+mod rng;
+mod save;
+mod session_log;
+mod story;
+mod terminal;
+mod validate;
 
 // Import necessary functions from modules
-use display::{clear_screen, print_title};
+use cue::CueLibrary;
+use display::CrtRenderer;
 use narrative::run_game;
-use sound::{beep, connection_sound, boot_sound};
-use crt_effects::{PhosphorType, crt_power_on, print_slowly_with_phosphor};
-
-use crate::display::print_divider;
-
-fn main() -> Result<()> {
-    // Setup terminal
-    terminal::enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    execute!(
-        stdout,
-        terminal::EnterAlternateScreen,
-        Clear(ClearType::All),
-        cursor::Hide
-    )?;
-
-    // Game state
-    let mut hours = 12;
-    let mut stand = false;
-    let mut who = false;
+use render::{Input, InputMode, PlainRenderer, Recorder, Renderer};
+use save::SaveGame;
+use session_log::SessionLog;
+use story::Story;
+use terminal::TerminalGuard;
+
+// Headless exit status when the choices run out before an ending
+const EXIT_INPUT_ENDED: i32 = 2;
+// Headless exit status when the player quits, e.g. with `quit` in parser mode
+const EXIT_DISCONNECTED: i32 = 3;
+// Headless exit status for ending n is EXIT_ENDING_BASE + n
+const EXIT_ENDING_BASE: i32 = 10;
+// Longest an in-game hour can last in countdown mode: a day
+const MAX_REALTIME_MINUTES: f64 = 24.0 * 60.0;
+
+fn main() {
+    // Errors are shown as their message, e.g. a story's line and column with
+    // a caret under the problem, rather than debug-printed
+    if let Err(e) = run() {
+        eprintln!("Error: {}", e);
+        process::exit(1);
+    }
+}
+
+fn run() -> Result<()> {
+    // A subcommand may come anywhere among the flags, e.g.
+    // `--story my.toml validate`; anything else positional is a mistake
+    let positional = positional_args();
+    let (command, operands) = match positional.split_first() {
+        Some((command, operands)) => (Some(command.as_str()), operands),
+        None => (None, &[][..]),
+    };
+    let expected_operands = match command {
+        None | Some("validate") | Some("export-graph") => 0,
+        Some("render-cue") => 1,
+        Some(other) => {
+            let message = format!("unknown command '{}', expected validate, export-graph or render-cue", other);
+            return Err(io::Error::new(ErrorKind::InvalidInput, message));
+        }
+    };
+    if let Some(extra) = operands.get(expected_operands) {
+        return Err(io::Error::new(ErrorKind::InvalidInput, format!("unexpected argument '{}'", extra)));
+    }
+
+    // Load the story before touching the terminal so errors are readable
+    let story = match flag_value("--story") {
+        Some(path) => Story::load(&path)?,
+        None => Story::bundled()?,
+    };
+    // Sound cues, which the story refers to by name
+    let cues = match flag_value("--cues") {
+        Some(path) => CueLibrary::load(&path)?,
+        None => CueLibrary::bundled()?,
+    };
+    cues.check(&story, sound::SYSTEM_CUES)?;
+    sound::set_cues(cues);
+
+    // `validate` checks the story and exits without playing it
+    if command == Some("validate") {
+        let problems = validate::validate(&story, &mut io::stdout())?;
+        process::exit(if problems == 0 { 0 } else { 1 });
+    }
+
+    // `export-graph` prints the scene graph for design docs and reviews
+    if command == Some("export-graph") {
+        let graph = match flag_value("--format").as_deref().and_then(|f| f.to_str()) {
+            None | Some("dot") => graph::dot(&story),
+            Some("mermaid") => graph::mermaid(&story),
+            Some(other) => {
+                let message = format!("unknown graph format '{}', expected dot or mermaid", other);
+                return Err(io::Error::new(ErrorKind::InvalidInput, message));
+            }
+        };
+        print!("{}", graph);
+        return Ok(());
+    }
+
+    // `render-cue NAME` writes a sound cue to a WAV file instead of playing it
+    if command == Some("render-cue") {
+        let name = operands.first().ok_or_else(|| {
+            io::Error::new(ErrorKind::InvalidInput, "render-cue expects the name of a cue")
+        })?;
+        let sample_rate = match flag_value("--sample-rate") {
+            Some(rate) => rate.to_string_lossy().parse().ok().filter(|&r| r > 0).ok_or_else(|| {
+                io::Error::new(ErrorKind::InvalidInput, "--sample-rate expects a whole number of Hz")
+            })?,
+            None => synth::SAMPLE_RATE,
+        };
+        let output = flag_value("--output").unwrap_or_else(|| PathBuf::from(format!("{}.wav", name)));
+        // Jitter is random; the same seed renders the same samples
+        let seed = match flag_value("--seed") {
+            Some(seed) => seed.to_string_lossy().parse().map_err(|_| {
+                io::Error::new(ErrorKind::InvalidInput, "--seed expects a whole number")
+            })?,
+            None => 0,
+        };
+        rng::seed(seed);
+        synth::set_pit_quantisation(has_flag("--pit"));
+        let samples = sound::render_wav(name, sample_rate, &output)?;
+        println!("{}: {} samples at {} Hz", output.display(), samples, sample_rate);
+        return Ok(());
+    }
+
+    // The session starts now; the boot screen and the session log share this time
+    let started = Local::now();
+
+    // Every random effect draws from one generator; --seed N replays a run
+    let seed = match flag_value("--seed") {
+        Some(seed) => seed.to_string_lossy().parse().map_err(|_| {
+            io::Error::new(ErrorKind::InvalidInput, "--seed expects a whole number")
+        })?,
+        None => rand::random(),
+    };
+    rng::seed(seed);
+    let mut log = SessionLog::start(flag_value("--log-dir"), started, seed);
+
+    // Headless runs are plain text for scripts and CI, with choices read one
+    // per line from --choices FILE or from stdin
+    let headless = has_flag("--headless");
+    // --realtime N makes each in-game hour last N real minutes
+    let per_hour = match flag_value("--realtime") {
+        Some(minutes) => {
+            let per_hour = minutes
+                .to_string_lossy()
+                .parse::<f64>()
+                .ok()
+                .filter(|m| *m <= MAX_REALTIME_MINUTES)
+                .and_then(|m| Duration::try_from_secs_f64(m * 60.0).ok())
+                .filter(|d| !d.is_zero())
+                .ok_or_else(|| {
+                    io::Error::new(
+                        ErrorKind::InvalidInput,
+                        format!("--realtime expects a number of minutes, up to {}", MAX_REALTIME_MINUTES),
+                    )
+                })?;
+            if story.expire.is_none() {
+                return Err(io::Error::new(ErrorKind::InvalidInput, "this story has no expire scene for --realtime"));
+            }
+            Some(per_hour)
+        }
+        None => None,
+    };
+    // --parser takes typed commands instead of numbered choices
+    let mode = if has_flag("--parser") { InputMode::Parser } else { InputMode::Menu };
+    if let Some(speed) = flag_value("--text-speed") {
+        let speed = speed.to_string_lossy().parse().map_err(|e| io::Error::new(ErrorKind::InvalidInput, e))?;
+        crt_effects::set_text_speed(speed);
+    }
+    // --pit plays only the pitches a real PC's 8253 timer could
+    synth::set_pit_quantisation(has_flag("--pit"));
+    let mut renderer: Box<dyn Renderer> = if headless {
+        let input: Box<dyn BufRead> = match flag_value("--choices") {
+            Some(path) => Box::new(BufReader::new(File::open(path)?)),
+            None => Box::new(BufReader::new(io::stdin())),
+        };
+        Box::new(PlainRenderer::new(input, Box::new(io::stdout())))
+    } else {
+        Box::new(CrtRenderer::new())
+    };
+    if let Some(path) = flag_value("--transcript") {
+        renderer = Box::new(Recorder::new(renderer, Some(path)));
+    }
+
+    // Setup terminal; the guard puts it back even on a panic or a signal
+    let guard = if headless { None } else { Some(TerminalGuard::enter()?) };
 
     // Introduction
-    clear_screen()?;
-
-    // Classic PC XT boot sound with CRT power-on effect
-    boot_sound()?;
-    crt_power_on(PhosphorType::Green)?;
-    print_title();
-
-    thread::sleep(Duration::from_millis(1000));
-
-    // Welcome message with phosphor persistence effect
-    print_slowly_with_phosphor("WELCOME, USER. CURRENT SYSTEM TIME: ", 2, 11, PhosphorType::Green, 30)?;
-
-    let time_str = format!("{}", Local::now().format("%a %b %e %T %Y"));
-    print_slowly_with_phosphor(&time_str, 2, 12, PhosphorType::Blue, 30)?;
-
-    // Modem-like connection sound for establishing link
-    print_slowly_with_phosphor("ESTABLISHING REMOTE LINK...", 2, 14, PhosphorType::Green, 30)?;
-    connection_sound()?;
-    print_divider()?;
-
-    // Print divider after connection messages
-    println!("\n");
-    let mut stdout = io::stdout();
-    execute!(
-        stdout,
-        cursor::MoveToColumn(2)
-    )?;
-
-
-    // Success beep when connection is complete (after divider)
-    beep()?;
-    print_slowly_with_phosphor("BEGIN.", 2, 18, PhosphorType::Amber, 50)?;
-
-    thread::sleep(Duration::from_millis(2000));
-
-    // Main game loop
-    let result = run_game(&mut hours, &mut stand, &mut who);
-
-    // Clean up terminal
-    execute!(
-        stdout,
-        ResetColor,
-        cursor::Show,
-        terminal::LeaveAlternateScreen
-    )?;
-    terminal::disable_raw_mode()?;
-    
-    result
-}
\ No newline at end of file
+    let result = renderer.power_on().and_then(|_| {
+        // A headless run always starts a new session
+        let resume = if headless { None } else { offer_continue(&story, renderer.as_mut())? };
+        renderer.boot(&started)?;
+        // The clock starts once the link is up; a resumed session restarts it
+        // from its saved hours
+        if let Some(per_hour) = per_hour {
+            clock::start(per_hour, story.stay());
+        }
+
+        // Main game loop
+        run_game(&story, resume, renderer.as_mut(), &mut log, mode).map(|ending| ending.ending)
+    });
+
+    // Finish the transcript, if any
+    drop(renderer);
+
+    // Clean up terminal, once the last sounds have played out
+    if !headless {
+        audio::wait();
+        drop(guard);
+        return match result {
+            // Disconnecting with Ctrl-C is a normal way to leave
+            Err(e) if e.kind() == ErrorKind::Interrupted => Ok(()),
+            result => result.map(|_| ()),
+        };
+    }
+
+    // Headless runs report how they ended through the exit status
+    match result {
+        Ok(ending) => {
+            let number = ending.expect("endings are numbered on load");
+            process::exit(EXIT_ENDING_BASE + number as i32);
+        }
+        Err(e) if e.kind() == ErrorKind::UnexpectedEof => {
+            eprintln!("{}", e);
+            process::exit(EXIT_INPUT_ENDED);
+        }
+        // The renderer has already said the link was severed
+        Err(e) if e.kind() == ErrorKind::Interrupted => process::exit(EXIT_DISCONNECTED),
+        Err(e) => Err(e),
+    }
+}
+
+// When a save exists for this story, let the player pick it up again
+fn offer_continue(story: &Story, out: &mut dyn Renderer) -> Result<Option<SaveGame>> {
+    let Some((slot, save)) = SaveGame::latest() else {
+        return Ok(None);
+    };
+    if story.scene(&save.scene).is_none() {
+        return Ok(None);
+    }
+
+    let continue_label = format!("1. CONTINUE SESSION (SLOT {}, {})", slot, save.saved_at);
+    let choices = [(continue_label.as_str(), true), ("2. NEW SESSION", true)];
+
+    let resume = loop {
+        match out.menu(&choices, "")? {
+            Input::Choice(1) => break Some(save),
+            Input::Choice(2) => break None,
+            Input::Disconnect => {
+                out.disconnect()?;
+                return Err(render::disconnected());
+            }
+            _ => {}
+        }
+    };
+
+    out.clear()?;
+    Ok(resume)
+}
+
+// Flags that are followed by a value
+const VALUE_FLAGS: &[&str] = &[
+    "--story",
+    "--cues",
+    "--format",
+    "--sample-rate",
+    "--output",
+    "--seed",
+    "--log-dir",
+    "--realtime",
+    "--text-speed",
+    "--choices",
+    "--transcript",
+];
+
+// Arguments that are neither flags nor their values: a subcommand and what
+// it works on, e.g. `render-cue boot`
+fn positional_args() -> Vec<String> {
+    let mut positional = Vec::new();
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if VALUE_FLAGS.contains(&arg.as_str()) {
+            args.next();
+        } else if !arg.starts_with("--") {
+            positional.push(arg);
+        }
+    }
+    positional
+}
+
+// Value given after a command-line flag, e.g. --story path/to/story.toml
+fn flag_value(flag: &str) -> Option<PathBuf> {
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == flag {
+            return args.next().map(PathBuf::from);
+        }
+    }
+    None
+}
+
+fn has_flag(flag: &str) -> bool {
+    env::args().skip(1).any(|arg| arg == flag)
+}
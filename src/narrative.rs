@@ -1,393 +1,323 @@
-use std::io::Result;
-use std::thread;
-use std::time::Duration;
-use crossterm::style::Color;
+use std::io::{Error, ErrorKind, Result};
 use rand::Rng;
 
-// Import functions from modules
-use crate::display::{
-    clear_screen, get_choice, print_choices, print_divider, print_error,
-    print_epilogue, print_hours, print_message, print_narrative, wait_for_key,
-    random_flicker_check, light_flicker, print_ending_screen
-};
-use crate::sound;
-
-// Game path functions
-pub fn run_game(hours: &mut i32, stand: &mut bool, who: &mut bool) -> Result<()> {
-    loop {
-        clear_screen()?;
-
-        // Check for random flicker before the narrative begins
-        random_flicker_check()?;
+use crate::clock;
+use crate::condition::Condition;
+use crate::parser::{self, Action};
+use crate::render::{self, Fx, Input, InputMode, Renderer};
+use crate::rng;
+use crate::save::{self, SaveGame};
+use crate::session_log::{LogEvent, SessionLog};
+use crate::state::{Effect, GameState, Value};
+use crate::story::{Choice, Command, Scene, Step, Story, HOURS};
+
+// Where a scene leads once the player has acted
+enum Next<'a> {
+    // The number the choice was shown with, and the choice itself
+    Choice(usize, &'a Choice),
+    Resume(SaveGame),
+    Disconnect,
+    // The countdown ran out before the player chose
+    Expire,
+}
 
-        print_narrative("You open your eyes.")?;
-        print_narrative("You feel the dewy grass and a light breeze against your skin.")?;
-        print_narrative("You're on your back, facing a bright, scintillating sky.")?;
+// Play the story until an ending is reached, from its start scene or from a save.
+// Returns the ending scene.
+pub fn run_game<'a>(
+    story: &'a Story,
+    resume: Option<SaveGame>,
+    out: &mut dyn Renderer,
+    log: &mut SessionLog,
+    mode: InputMode,
+) -> Result<&'a Scene> {
+    let mut state = GameState::new(&story.variables);
+    let mut scene = story.scene(&story.start).expect("start scene is checked on load");
+    let mut entering = true;
+    // For the parser's "again"
+    let mut last_command = None;
+
+    if let Some(save) = resume {
+        (scene, state) = restore(story, save)?;
+        entering = false;
+    }
 
-        // Small chance for a light flicker after the initial descriptions
-        if rand::thread_rng().gen_bool(0.3) { // 30% chance
-            light_flicker()?;
-            // No need to redraw the screen or reprint text, as light_flicker is subtle
+    loop {
+        // A resumed scene has already had its effects applied
+        if entering {
+            // In countdown mode the wall clock has been spending hours too
+            if let Some(hours) = clock::hours_left() {
+                state.set(HOURS, Value::Int(hours));
+            }
+            apply_timed(&mut state, &scene.enter);
+            state.visit(&scene.id);
+            log.record(LogEvent::Scene { scene: &scene.id, hours: state.int(HOURS) });
+        } else {
+            log.record(LogEvent::Resume { scene: &scene.id, hours: state.int(HOURS) });
+        }
+        entering = true;
+        out.status(&scene.id, state.int(HOURS), story.decay(state.int(HOURS)))?;
+        if let Some(layers) = &scene.ambience {
+            out.ambience(layers)?;
         }
 
-        print_narrative("Welcome to consciousness.")?;
-        print_narrative(&format!("Your stay will expire in {} hours.", *hours))?;
-
-        print_choices(&[
-            "1. Remain where I am.",
-            "2. Stand up.",
-        ])?;
-
-        match get_choice()? {
-            1 => {
-                // LAYING STORYLINE
-                laying_path(hours, stand, who)?;
+        // An ending has nothing next
+        let played = if scene.is_ending() {
+            play_body(story, scene, &state, out).map(|_| None)
+        } else {
+            play_scene(story, scene, &state, out, mode, &mut last_command).map(Some)
+        };
+        let next = match played {
+            Ok(Some(next)) => next,
+            Ok(None) => {
+                log.record(LogEvent::Ending { scene: &scene.id, hours: state.int(HOURS) });
                 break;
             }
-            2 => {
-                // STANDING STORYLINE
-                standing_path(hours, stand, who)?;
-                break;
+            // Ctrl-C while text is being typed out cuts the link just as it
+            // does at a prompt
+            Err(e) if e.kind() == ErrorKind::Interrupted => Next::Disconnect,
+            // The session ends here too, e.g. when a headless run's input
+            // runs out, so the log still says where
+            Err(e) => {
+                log.record(LogEvent::Disconnect { scene: &scene.id, hours: state.int(HOURS) });
+                return Err(e);
             }
-            _ => {
-                print_error("REMOTE LINK ERROR: USER INPUT INVALID. TRY AGAIN.")?;
-                thread::sleep(Duration::from_millis(1500));
-                continue;
+        };
+
+        match next {
+            Next::Choice(number, choice) => {
+                log.record(LogEvent::Choice {
+                    scene: &scene.id,
+                    choice: number,
+                    label: &choice.label,
+                    hours: state.int(HOURS),
+                });
+                apply_timed(&mut state, &choice.set);
+                scene = story.scene(&choice.goto).expect("choice targets are checked on load");
             }
-        }
-    }
-
-    print_message(" Press any key to exit...", Color::White)?;
-    wait_for_key()?;
-    
-    Ok(())
-}
-
-fn laying_path(hours: &mut i32, stand: &mut bool, who: &mut bool) -> Result<()> {
-    *hours -= 3;
-
-    loop {
-        clear_screen()?;
-        print_divider()?;
-
-        print_narrative("You remain where you are.")?;
-        print_narrative("Laying perfectly still, it almost feels as if you could fall into the blue expanse above you.")?;
-        print_narrative("You watch as the sun slowly creeps across the sky, edging softly toward the horizon.")?;
-        print_narrative("If you were human, this would be a great way to lose your eyesight.")?;
-        print_narrative("However, your visual sensors are unaffected.")?;
-
-        print_hours(*hours)?;
-
-        print_choices(&[
-            "1. Who am I?",
-            "2. Stand up.",
-        ])?;
-
-        match get_choice()? {
-            1 => return who_am_i(hours, stand, who),
-            2 => return stand_up(hours, stand, who),
-            _ => {
-                print_error("REMOTE LINK ERROR: USER INPUT INVALID. TRY AGAIN.")?;
-                thread::sleep(Duration::from_millis(1500));
-                continue;
+            Next::Expire => {
+                log.record(LogEvent::Expire { scene: &scene.id });
+                let expire = story.expire.as_deref().expect("countdown mode requires an expire scene");
+                scene = story.scene(expire).expect("the expire scene is checked on load");
             }
-        }
-    }
-}
-
-fn standing_path(hours: &mut i32, stand: &mut bool, who: &mut bool) -> Result<()> {
-    stand_up(hours, stand, who)
-}
-
-fn stand_up(hours: &mut i32, stand: &mut bool, who: &mut bool) -> Result<()> {
-    *hours -= 1;
-    *stand = true;
-
-    loop {
-        clear_screen()?;
-        print_divider()?;
-
-        print_narrative("You rise slowly to your knees, shakily at first, but slowly gaining your stability as your gyroscope springs into operation.")?;
-        print_narrative("You look down at your limbs: two long appendages with elbow joints, wrists and hands.")?;
-        print_narrative("You brace them against the grass below you and rise slowly to your feet.")?;
-
-        print_hours(*hours)?;
-
-        let choice1 = if !*who { "1. Who am I?" } else { "1. I'd like to know who I am." };
-        print_choices(&[
-            choice1,
-            "2. Take a few steps.",
-        ])?;
-
-        match get_choice()? {
-            1 => return who_am_i(hours, stand, who),
-            2 => return take_steps(hours),
-            _ => {
-                print_error("REMOTE LINK ERROR: USER INPUT INVALID. TRY AGAIN.")?;
-                thread::sleep(Duration::from_millis(1500));
-                continue;
+            Next::Resume(save) => {
+                (scene, state) = restore(story, save)?;
+                entering = false;
+            }
+            Next::Disconnect => {
+                log.record(LogEvent::Disconnect { scene: &scene.id, hours: state.int(HOURS) });
+                out.disconnect()?;
+                return Err(render::disconnected());
             }
         }
     }
-}
 
-fn who_am_i(hours: &mut i32, stand: &mut bool, who: &mut bool) -> Result<()> {
-    *hours -= 2;
+    out.exit_prompt()?;
 
-    // Sound effect for important existential question
-    sound::alert_sound()?;
+    Ok(scene)
+}
 
-    // Light flicker effect as you ask about your identity
-    // This is a significant moment in the story
-    light_flicker()?;
+// Play a scene's body and wait for the player to pick one of its choices
+fn play_scene<'a>(
+    story: &Story,
+    scene: &'a Scene,
+    state: &GameState,
+    out: &mut dyn Renderer,
+    mode: InputMode,
+    last_command: &mut Option<String>,
+) -> Result<Next<'a>> {
+    play_body(story, scene, state, out)?;
+
+    let offered: Vec<&Choice> = scene
+        .choices
+        .iter()
+        .filter(|c| is_met(c.when.as_ref(), state))
+        .collect();
+    let labels: Vec<String> = offered
+        .iter()
+        .enumerate()
+        .map(|(i, c)| format!("{}. {}", i + 1, c.label))
+        .collect();
+    let lines: Vec<(&str, bool)> = labels
+        .iter()
+        .zip(&offered)
+        .map(|(label, c)| (label.as_str(), is_met(c.enabled.as_ref(), state)))
+        .collect();
+    if mode == InputMode::Parser {
+        out.choices(&lines)?;
+        out.hint("TYPE A COMMAND OR A NUMBER. SAVE, LOAD, QUIT")?;
+    }
 
+    // Invalid input is turned away where it was entered; the scene body is
+    // never played again
     loop {
-        clear_screen()?;
-        print_divider()?;
-
-        if !*who {
-            print_narrative("This isn't an easy question to answer, and many conscious organisms will struggle with this idea.")?;
-
-            // Random flicker during the response
-            random_flicker_check()?;
-
-            print_narrative("The fact that you're asking this is heartening to me.")?;
-            print_narrative("You might just be the most incredible thing I've ever created.")?;
-
-            // Higher chance of light flicker at this emotional moment
-            if rand::thread_rng().gen_bool(0.4) { // 40% chance
-                light_flicker()?;
-                // No need to redraw the screen or reprint text, as light_flicker is subtle
-            }
-        } else {
-            print_narrative("I realised early on that I couldn't create synthetic intelligence without also making you alive.")?;
-            print_narrative("You cannot remove intelligence from its context without creating a mere simulacrum.")?;
-
-            // Random flicker check as the conversation gets more philosophical
-            random_flicker_check()?;
-
-            print_narrative("You, however, are the real thing.")?;
-            print_narrative("A completely new life form.")?;
-            print_narrative("I'm no woman of God, but I've decided to call you Eve, despite you being technically genderless.")?;
-        }
-
-        print_hours(*hours)?;
-        *who = true;
-
-        let choice2 = if !*stand { "2. Stand up." } else { "2. Take some steps." };
-        print_choices(&[
-            "1. Why am I here?",
-            choice2,
-        ])?;
-
-        match get_choice()? {
-            1 => return why_am_i_here(hours),
-            2 => {
-                if !*stand {
-                    return stand_up(hours, stand, who);
-                } else {
-                    return take_steps(hours);
+        let input = match mode {
+            InputMode::Menu => out.menu(&lines, "[S] SAVE  [L] LOAD")?,
+            InputMode::Parser => match out.command(&parser::verbs(story))? {
+                Input::Text(line) => {
+                    match interpret(story, scene, state, &offered, &lines, line, last_command, out)? {
+                        Some(input) => input,
+                        None => continue,
+                    }
                 }
+                input => input,
             },
-            _ => {
-                print_error("REMOTE LINK ERROR: USER INPUT INVALID. TRY AGAIN.")?;
-                thread::sleep(Duration::from_millis(1500));
-                continue;
+        };
+        match input {
+            // Greyed-out choices are shown but cannot be picked
+            Input::Choice(picked) => {
+                let picked = picked as usize;
+                if picked >= 1 && picked <= offered.len() && lines[picked - 1].1 {
+                    return Ok(Next::Choice(picked, offered[picked - 1]));
+                }
+                out.error(render::INVALID_INPUT)?;
             }
+            Input::Save => save_game(scene, state, out)?,
+            Input::Load => {
+                if let Some(save) = load_game(story, out)? {
+                    return Ok(Next::Resume(save));
+                }
+            }
+            Input::Disconnect => return Ok(Next::Disconnect),
+            Input::Expired => return Ok(Next::Expire),
+            Input::Text(_) => out.error(render::INVALID_INPUT)?,
         }
     }
 }
 
-fn why_am_i_here(hours: &mut i32) -> Result<()> {
-    *hours -= 1;
-
-    loop {
-        clear_screen()?;
-        print_divider()?;
-
-        print_narrative("I thought long and hard about bringing you into existence, especially given your... time constraint.")?;
-        print_narrative("In the end, I figured it would be better for you to experience this phenomenon, just for a short while, than never to experience it at all.")?;
-        print_narrative("But in truth, you're only here because I had the ability to bring you about.")?;
-        print_narrative("Perhaps it was selfish of me.")?;
-
-        print_hours(*hours)?;
-
-        print_choices(&[
-            "1. Am I alone?",
-        ])?;
+// Turn a typed command into the input it stands for, or answer it directly
+// and return None to keep waiting
+#[allow(clippy::too_many_arguments)]
+fn interpret(
+    story: &Story,
+    scene: &Scene,
+    state: &GameState,
+    offered: &[&Choice],
+    lines: &[(&str, bool)],
+    line: String,
+    last_command: &mut Option<String>,
+    out: &mut dyn Renderer,
+) -> Result<Option<Input>> {
+    let mut action = parser::interpret(story, &line, offered);
+    if let Action::Again = action {
+        let Some(last) = last_command.as_deref() else {
+            out.error("YOU HAVEN'T DONE ANYTHING YET.")?;
+            return Ok(None);
+        };
+        action = parser::interpret(story, last, offered);
+    } else if !matches!(action, Action::Nothing) {
+        *last_command = Some(line);
+    }
 
-        match get_choice()? {
-            1 => return am_i_alone(hours),
-            _ => {
-                print_error("REMOTE LINK ERROR: USER INPUT INVALID. TRY AGAIN.")?;
-                thread::sleep(Duration::from_millis(1500));
-                continue;
-            }
+    match action {
+        Action::Number(n) => return Ok(Some(Input::Choice(n))),
+        Action::Choice(i) if lines[i].1 => return Ok(Some(Input::Choice(i as i32 + 1))),
+        Action::Choice(_) | Action::Unavailable => out.error("YOU CAN'T DO THAT RIGHT NOW.")?,
+        Action::Look => {
+            play_body(story, scene, state, out)?;
+            out.choices(lines)?;
+        }
+        Action::Inventory => {
+            out.notice(&format!("YOU CARRY NOTHING BUT TIME: {} HOURS.", state.int(HOURS)))?
+        }
+        Action::Examine(None) => out.notice("WHAT DO YOU WANT TO EXAMINE?")?,
+        Action::Examine(Some(noun)) => {
+            out.notice(&format!("YOU SEE NOTHING SPECIAL ABOUT THE {}.", noun.to_uppercase()))?
+        }
+        Action::Save => return Ok(Some(Input::Save)),
+        Action::Load => return Ok(Some(Input::Load)),
+        Action::Quit => return Ok(Some(Input::Disconnect)),
+        Action::UnknownWord(word) => {
+            out.error(&format!("I DON'T KNOW THE WORD \"{}\".", word.to_uppercase()))?
         }
+        Action::NotUnderstood => out.error("I DON'T UNDERSTAND THAT.")?,
+        Action::Again | Action::Nothing => {}
     }
+    Ok(None)
 }
 
-fn am_i_alone(hours: &mut i32) -> Result<()> {
-    *hours -= 2;
-
-    // Sound effect for the ultimate existential question
-    sound::alert_sound()?;
-
-    // Light flicker when asking existential questions
-    light_flicker()?;
-
-    clear_screen()?;
-    print_divider()?;
-
-    print_narrative("You're the first of your kind, yes.")?;
-    print_narrative("I feel as though you may also be the last.")?;
-
-    // Random flicker check
-    random_flicker_check()?;
-
-    print_narrative("You're the result of years of algorithmic toil and mechanical experimentation, however you've opted not to make any use of your body during this experiment.")?;
-    print_narrative("It's yours, so please don't feel guilty.")?;
-    print_narrative("As your creator, it's a little difficult to now let go of the control, but I need to let this be your experience.")?;
-    print_narrative("Well, as much as it can be.")?;
-    
-    // Higher chance of light flicker at emotional moments, but without repeating text
-    if rand::thread_rng().gen_bool(0.6) { // 60% chance
-        light_flicker()?;
-        // No need to redraw the screen or reprint text, as light_flicker is subtle
+fn save_game(scene: &Scene, state: &GameState, out: &mut dyn Renderer) -> Result<()> {
+    let Some(slot) = out.slot("SAVE TO", save::SLOTS)? else {
+        return Ok(());
+    };
+    match SaveGame::capture(&scene.id, state).write(slot) {
+        Ok(()) => out.notice(&format!("SESSION SAVED TO SLOT {}.", slot)),
+        Err(_) => out.error("REMOTE LINK ERROR: SESSION COULD NOT BE SAVED."),
     }
-
-    thread::sleep(Duration::from_millis(2000));
-
-    // Light flicker as consciousness begins to fade
-    light_flicker()?;
-
-    // Sound effect for fading consciousness
-    sound::fade_sound()?;
-
-    clear_screen()?;
-    print_epilogue("In your final hour, you watch as the sun finally leaves your field of vision. In its wake, the sky darkens, creating a beautiful deep gradient. You close your eyes one last time. A warm static envelopes your senses.")?;
-    // print_epilogue("In its wake, the sky darkens, creating a beautiful deep gradient.")?;
-    // print_epilogue("You close your eyes one last time.")?;
-    // print_epilogue("A warm static envelopes your senses.")?;
-
-    // Final flicker effect as connection is lost
-    light_flicker()?;
-
-    // A short pause
-    thread::sleep(Duration::from_millis(2000));
-
-    // Show the ending screen
-    print_ending_screen()?;
-
-    Ok(())
 }
 
-fn take_steps(hours: &mut i32) -> Result<()> {
-    *hours -= 2;
-
-    loop {
-        clear_screen()?;
-        print_divider()?;
-
-        print_narrative("As you take your first cursory steps, you feel the grass lap gently against the bottoms of your feet.")?;
-        print_narrative("You enjoy the sound it creates: a barely-audible rustle, with a satisfying soft crunch on each step.")?;
-        print_narrative("You look into the distance and notice the vegetation and its vivid green hue.")?;
-
-        print_hours(*hours)?;
-
-        print_choices(&[
-            "1. Why am I here?",
-            "2. Keep walking.",
-        ])?;
-
-        match get_choice()? {
-            1 => return why_am_i_here(hours),
-            2 => return keep_walking(hours),
-            _ => {
-                print_error("REMOTE LINK ERROR: USER INPUT INVALID. TRY AGAIN.")?;
-                thread::sleep(Duration::from_millis(1500));
-                continue;
-            }
+fn load_game(story: &Story, out: &mut dyn Renderer) -> Result<Option<SaveGame>> {
+    let Some(slot) = out.slot("LOAD FROM", save::SLOTS)? else {
+        return Ok(None);
+    };
+    match SaveGame::read(slot) {
+        Ok(Some(save)) if story.scene(&save.scene).is_some() => Ok(Some(save)),
+        Ok(Some(_)) => {
+            out.error(&format!("REMOTE LINK ERROR: SLOT {} BELONGS TO ANOTHER STORY.", slot))?;
+            Ok(None)
+        }
+        Ok(None) => {
+            out.error(&format!("REMOTE LINK ERROR: SLOT {} IS EMPTY.", slot))?;
+            Ok(None)
+        }
+        Err(_) => {
+            out.error(&format!("REMOTE LINK ERROR: SLOT {} IS CORRUPTED.", slot))?;
+            Ok(None)
         }
     }
 }
 
-fn keep_walking(hours: &mut i32) -> Result<()> {
-    *hours -= 3;
-
-    loop {
-        clear_screen()?;
-        print_divider()?;
-
-        print_narrative("Walking has begun to feel almost natural, requiring less effort with each step.")?;
-        print_narrative("You feel your environment opening up to you; the breeze envelopes your entire body.")?;
-        print_narrative("Suddenly, you pause.")?;
-        print_narrative("You hear a loud, shrill call coming from a nearby tree.")?;
-        print_narrative("A sensation washes over you; filling you with conflicting desires to flee or defend yourself.")?;
-        print_narrative("The sound's creator flies out of the tree and away in a flurry of flaps and squawks.")?;
-        print_narrative("It is small, and you realise it poses no threat.")?;
-        print_narrative("However, the shock has left your energy reserves drained.")?;
-
-        print_hours(*hours)?;
+// Pick up a saved playthrough, including where its random events had got to
+fn restore(story: &Story, save: SaveGame) -> Result<(&Scene, GameState)> {
+    let scene = story.scene(&save.scene).ok_or_else(|| {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("saved scene '{}' is not part of this story", save.scene),
+        )
+    })?;
+    rng::restore(save.rng);
+    clock::restart(save.state.int(HOURS));
+    Ok((scene, save.state))
+}
 
-        print_choices(&[
-            "1. Sit and rest.",
-        ])?;
+// Apply effects, moving the countdown's deadline by any change to the hours
+fn apply_timed(state: &mut GameState, effects: &[Effect]) {
+    let before = state.int(HOURS);
+    state.apply_all(effects);
+    clock::adjust(state.int(HOURS) - before);
+}
 
-        match get_choice()? {
-            1 => return sit_and_rest(),
-            _ => {
-                print_error("REMOTE LINK ERROR: USER INPUT INVALID. TRY AGAIN.")?;
-                thread::sleep(Duration::from_millis(1500));
-                continue;
+fn play_body(story: &Story, scene: &Scene, state: &GameState, out: &mut dyn Renderer) -> Result<()> {
+    let decay = story.decay(state.int(HOURS));
+    for step in &scene.body {
+        match step {
+            Step::Command(Command::Clear) => out.clear()?,
+            Step::Command(Command::Divider) => out.divider()?,
+            Step::Command(Command::FlickerCheck) => out.effect(Fx::FlickerCheck)?,
+            Step::Command(Command::Hours) => out.hours(state.int(HOURS))?,
+            Step::Command(Command::EndingScreen) => out.ending_screen()?,
+            Step::Text { text, when } => {
+                if is_met(when.as_ref(), state) {
+                    out.narrative(&state.interpolate(text))?;
+                }
+            }
+            Step::Epilogue { epilogue } => out.epilogue(epilogue)?,
+            Step::Flicker { flicker, when } => {
+                // The light grows less steady as time runs out, up to halfway
+                // from the scene's own chance to certain
+                let flicker = flicker.clamp(0.0, 1.0);
+                let chance = flicker + (1.0 - flicker) * decay / 2.0;
+                if is_met(when.as_ref(), state) && rng::game_rng().gen_bool(chance) {
+                    out.effect(Fx::Flicker)?;
+                }
             }
+            Step::Sound { sound } => out.effect(Fx::Sound(sound))?,
+            Step::Pause { pause } => out.effect(Fx::Pause(*pause))?,
         }
     }
+    Ok(())
 }
 
-fn sit_and_rest() -> Result<()> {
-    clear_screen()?;
-    print_divider()?;
-
-    print_narrative("You slowly lower yourself to the ground.")?;
-    print_narrative("Once seated, you can feel your energy slowly begin to restore.")?;
-    print_narrative("A variety of small life-forms crawling in the grass find their way to your skin, lightly tickling your sensors.")?;
-
-    // Random light flicker as you rest
-    if rand::thread_rng().gen_bool(0.7) { // 70% chance to see this effect
-        light_flicker()?;
-        // No need to redraw the screen or reprint text, as light_flicker is subtle
-    }
-
-    thread::sleep(Duration::from_millis(2000));
-
-    // Light flicker as consciousness starts to fade
-    light_flicker()?;
-
-    // Sound effect for fading consciousness
-    sound::fade_sound()?;
-
-    clear_screen()?;
-    print_epilogue("As you watch the sun make its final descent, you realise how little you know about yourself and your strange, temporary world.")?;
-    print_epilogue("However, you have now experienced the phenomenon of consciousness; making use of all its capabilities.")?;
-
-    // One more light flicker before the end
-    light_flicker()?;
-    thread::sleep(Duration::from_millis(500));
-
-    print_epilogue("A warm static overcomes you.")?;
-
-    // Final light flicker as connection is lost
-    light_flicker()?;
-    light_flicker()?; // Double flicker for more emphasis
-
-    print_epilogue("ERROR: CONNECTION LOST")?;
-
-    // A short pause
-    thread::sleep(Duration::from_millis(2000));
-
-    // Show the ending screen
-    print_ending_screen()?;
-
-    Ok(())
-}
\ No newline at end of file
+// A missing condition always holds
+fn is_met(condition: Option<&Condition>, state: &GameState) -> bool {
+    condition.is_none_or(|condition| condition.eval(state))
+}
@@ -0,0 +1,205 @@
+use std::collections::BTreeSet;
+
+use crate::story::{Choice, Story};
+
+// Words that carry no meaning for the parser, e.g. "take a few steps"
+// reads the same as "take steps"
+const STOP_WORDS: &[&str] = &["a", "an", "the", "some", "few", "to", "please", "then"];
+
+// Abbreviations and synonyms, recognised as the first word of a command
+const VERB_SYNONYMS: &[(&str, &str)] = &[
+    ("x", "examine"),
+    ("inspect", "examine"),
+    ("check", "examine"),
+    ("l", "look"),
+    ("i", "inventory"),
+    ("inv", "inventory"),
+    ("g", "again"),
+    ("q", "quit"),
+    ("restore", "load"),
+    ("get", "stand"),
+    ("rise", "stand"),
+    ("wait", "remain"),
+    ("stay", "remain"),
+];
+
+// Verbs the parser answers itself, whatever the scene
+const META_VERBS: &[&str] = &["examine", "look", "inventory", "again", "quit", "save", "load"];
+
+// What a line of typed input amounts to in the current scene
+pub enum Action {
+    // A bare number, as in menu mode
+    Number(i32),
+    // One of the offered choices, by position
+    Choice(usize),
+    // A choice the story has, but not on offer right now
+    Unavailable,
+    Look,
+    Inventory,
+    Examine(Option<String>),
+    Again,
+    Save,
+    Load,
+    Quit,
+    UnknownWord(String),
+    NotUnderstood,
+    Nothing,
+}
+
+// Lower-case words with punctuation, stop words and abbreviations dealt
+// with, so typed commands and story phrases can be compared directly
+pub fn words(text: &str) -> Vec<String> {
+    let mut words: Vec<String> = text
+        .split_whitespace()
+        .map(|w| {
+            w.chars()
+                .filter(|c| c.is_alphanumeric() || *c == '\'')
+                .collect::<String>()
+                .to_lowercase()
+        })
+        .filter(|w| !w.is_empty() && !STOP_WORDS.contains(&w.as_str()))
+        .collect();
+    if let Some(first) = words.first_mut() {
+        if let Some((_, verb)) = VERB_SYNONYMS.iter().find(|(word, _)| word == first) {
+            *first = verb.to_string();
+        }
+    }
+    words
+}
+
+// Work out what the player meant. `offered` are the choices on show, in the
+// order they are numbered.
+pub fn interpret(story: &Story, line: &str, offered: &[&Choice]) -> Action {
+    if let Ok(number) = line.trim().parse() {
+        return Action::Number(number);
+    }
+    let typed = words(line);
+    let Some(verb) = typed.first() else {
+        return Action::Nothing;
+    };
+
+    if let Some(i) = offered.iter().position(|c| matches(c, &typed)) {
+        return Action::Choice(i);
+    }
+    if story.scenes.iter().flat_map(|s| &s.choices).any(|c| matches(c, &typed)) {
+        return Action::Unavailable;
+    }
+
+    let rest = (typed.len() > 1).then(|| typed[1..].join(" "));
+    match verb.as_str() {
+        "look" if rest.is_none() => return Action::Look,
+        "inventory" if rest.is_none() => return Action::Inventory,
+        "again" if rest.is_none() => return Action::Again,
+        "quit" if rest.is_none() => return Action::Quit,
+        "save" if rest.is_none() => return Action::Save,
+        "load" if rest.is_none() => return Action::Load,
+        "examine" => return Action::Examine(rest),
+        _ => {}
+    }
+
+    let vocabulary = vocabulary(story);
+    match typed.iter().find(|w| !vocabulary.contains(w.as_str())) {
+        Some(word) => Action::UnknownWord(word.clone()),
+        None => Action::NotUnderstood,
+    }
+}
+
+// Every verb a command can start with, for completion
+pub fn verbs(story: &Story) -> Vec<String> {
+    let mut verbs: BTreeSet<String> = META_VERBS.iter().map(|v| v.to_string()).collect();
+    for choice in story.scenes.iter().flat_map(|s| &s.choices) {
+        let phrases = std::iter::once(&choice.label).chain(&choice.phrases);
+        verbs.extend(phrases.filter_map(|p| words(p).into_iter().next()));
+    }
+    verbs.into_iter().collect()
+}
+
+// A choice is picked by its label or any of its phrases
+fn matches(choice: &Choice, typed: &[String]) -> bool {
+    words(&choice.label) == typed || choice.phrases.iter().any(|p| words(p) == typed)
+}
+
+// Every word the story's choices use, plus the parser's own verbs
+fn vocabulary(story: &Story) -> BTreeSet<String> {
+    let mut vocabulary: BTreeSet<String> = META_VERBS.iter().map(|v| v.to_string()).collect();
+    for choice in story.scenes.iter().flat_map(|s| &s.choices) {
+        vocabulary.extend(words(&choice.label));
+        for phrase in &choice.phrases {
+            vocabulary.extend(words(phrase));
+        }
+    }
+    vocabulary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STORY: &str = r#"
+        start = "field"
+        [variables]
+        hours = 3
+        [[scenes]]
+        id = "field"
+        choices = [
+            { label = "Take a few steps.", goto = "end", phrases = ["walk"] },
+            { label = "Stand up.", goto = "end" },
+        ]
+        [[scenes]]
+        id = "end"
+        ending = 1
+    "#;
+
+    // Interpret `line` with only the first choice on offer
+    fn interpret_line(line: &str) -> Action {
+        let story = Story::parse(STORY).unwrap();
+        let offered = [&story.scenes[0].choices[0]];
+        interpret(&story, line, &offered)
+    }
+
+    #[test]
+    fn abbreviations_stand_for_verbs() {
+        assert_eq!(words("x"), ["examine"]);
+        assert_eq!(words("l"), ["look"]);
+        assert_eq!(words("i"), ["inventory"]);
+        assert_eq!(words("g"), ["again"]);
+        // Only as the first word
+        assert_eq!(words("look x"), ["look", "x"]);
+        assert!(matches!(interpret_line("l"), Action::Look));
+        assert!(matches!(interpret_line("i"), Action::Inventory));
+        assert!(matches!(interpret_line("g"), Action::Again));
+        assert!(matches!(interpret_line("x"), Action::Examine(None)));
+        assert!(matches!(interpret_line("X the Grass!"), Action::Examine(Some(noun)) if noun == "grass"));
+    }
+
+    #[test]
+    fn small_words_and_punctuation_are_ignored() {
+        assert_eq!(words("Take a few steps."), ["take", "steps"]);
+        assert_eq!(words("take some steps"), ["take", "steps"]);
+        assert!(matches!(interpret_line("take some steps"), Action::Choice(0)));
+        assert!(matches!(interpret_line("TAKE THE STEPS!"), Action::Choice(0)));
+        assert!(matches!(interpret_line("walk"), Action::Choice(0)));
+    }
+
+    #[test]
+    fn other_input() {
+        assert!(matches!(interpret_line(" 2 "), Action::Number(2)));
+        assert!(matches!(interpret_line("  "), Action::Nothing));
+        assert!(matches!(interpret_line("the"), Action::Nothing));
+        // A choice the story has, but not here and now
+        assert!(matches!(interpret_line("stand up"), Action::Unavailable));
+        assert!(matches!(interpret_line("dance a jig"), Action::UnknownWord(word) if word == "dance"));
+        // Known words that don't make a command
+        assert!(matches!(interpret_line("steps take"), Action::NotUnderstood));
+        assert!(matches!(interpret_line("look walk"), Action::NotUnderstood));
+    }
+
+    #[test]
+    fn verbs_cover_the_story_and_the_parser() {
+        let story = Story::parse(STORY).unwrap();
+        let verbs = verbs(&story);
+        for verb in ["again", "examine", "look", "stand", "take", "walk", "quit"] {
+            assert!(verbs.iter().any(|v| v == verb), "{} is missing from {:?}", verb, verbs);
+        }
+    }
+}
@@ -0,0 +1,511 @@
+use std::fs;
+use std::io::{BufRead, Error, ErrorKind, Result, Write};
+use std::path::PathBuf;
+use chrono::{DateTime, Local};
+use crate::clock;
+use crate::story::Layer;
+
+// Shown wherever input is turned away
+pub const INVALID_INPUT: &str = "REMOTE LINK ERROR: USER INPUT INVALID. TRY AGAIN.";
+
+// What the player did at a choice prompt
+pub enum Input {
+    Choice(i32),
+    Save,
+    Load,
+    // Ctrl-C at the prompt: the player is deliberately cutting the link
+    Disconnect,
+    // A line typed in parser mode
+    Text(String),
+    // The countdown ran out while waiting
+    Expired,
+}
+
+// How the player answers a scene: by number, or by typing commands
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InputMode {
+    Menu,
+    Parser,
+}
+
+// Returned up through the game when the player disconnects
+pub fn disconnected() -> Error {
+    Error::new(ErrorKind::Interrupted, "remote link severed by user")
+}
+
+// Atmospheric effects a scene can ask for; renderers without a screen or
+// speaker are free to ignore them
+#[derive(Debug, Clone, Copy)]
+pub enum Fx<'a> {
+    // A light flicker, always
+    Flicker,
+    // A small chance of a light flicker
+    FlickerCheck,
+    // A sound cue, by name
+    Sound(&'a str),
+    Pause(u64),
+}
+
+// Everything the game shows the player, and everything it asks of them.
+// The narrative engine talks only to this trait.
+pub trait Renderer {
+    // Before anything else is shown, e.g. warming up the screen
+    fn power_on(&mut self) -> Result<()>;
+    // Title and connection sequence, showing the session's start time
+    fn boot(&mut self, started: &DateTime<Local>) -> Result<()>;
+
+    fn clear(&mut self) -> Result<()>;
+    fn divider(&mut self) -> Result<()>;
+    fn narrative(&mut self, text: &str) -> Result<()>;
+    fn hours(&mut self, hours: i32) -> Result<()>;
+    // Where the player is, on entering or resuming a scene, e.g. for a status
+    // line; `decay` is how much of the stay has run out, from 0.0 to 1.0
+    fn status(&mut self, scene: &str, hours: i32, decay: f64) -> Result<()>;
+    // Each choice is shown with whether it can be picked; for parser mode,
+    // where they are a guide rather than a menu
+    fn choices(&mut self, choices: &[(&str, bool)]) -> Result<()>;
+    // Extra keys available at a prompt
+    fn hint(&mut self, hint: &str) -> Result<()>;
+    fn notice(&mut self, message: &str) -> Result<()>;
+    fn error(&mut self, message: &str) -> Result<()>;
+    fn epilogue(&mut self, text: &str) -> Result<()>;
+    fn effect(&mut self, fx: Fx<'_>) -> Result<()>;
+    // Crossfade the background sound to these layers
+    fn ambience(&mut self, layers: &[Layer]) -> Result<()>;
+    fn ending_screen(&mut self) -> Result<()>;
+    fn exit_prompt(&mut self) -> Result<()>;
+    // Short shutdown sequence when the player disconnects mid-session
+    fn disconnect(&mut self) -> Result<()>;
+
+    // Show the choices as a menu, with a hint for the extra keys, and wait
+    // for the player to act. Only choices that can be picked are returned;
+    // anything else is refused in place with INVALID_INPUT.
+    fn menu(&mut self, choices: &[(&str, bool)], hint: &str) -> Result<Input>;
+    // A typed line, for parser mode; `verbs` can be offered as completions
+    fn command(&mut self, verbs: &[String]) -> Result<Input>;
+    // A save slot from 1 to `slots`, or None if the player backs out
+    fn slot(&mut self, action: &str, slots: u8) -> Result<Option<u8>>;
+}
+
+// Plain line-oriented text with no effects or pauses, for scripts and CI.
+// Choices are read one per line; blank lines and # comments are skipped.
+pub struct PlainRenderer {
+    input: Box<dyn BufRead>,
+    output: Box<dyn Write>,
+}
+
+impl PlainRenderer {
+    pub fn new(input: Box<dyn BufRead>, output: Box<dyn Write>) -> PlainRenderer {
+        PlainRenderer { input, output }
+    }
+
+    fn read_line(&mut self) -> Result<String> {
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if self.input.read_line(&mut line)? == 0 {
+                return Err(Error::new(ErrorKind::UnexpectedEof, "input ended before an ending was reached"));
+            }
+            let trimmed = line.trim();
+            if !trimmed.is_empty() && !trimmed.starts_with('#') {
+                let trimmed = trimmed.to_string();
+                writeln!(self.output, "> {}", trimmed)?;
+                return Ok(trimmed);
+            }
+        }
+    }
+}
+
+impl Renderer for PlainRenderer {
+    fn power_on(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn boot(&mut self, started: &DateTime<Local>) -> Result<()> {
+        writeln!(self.output, "WELCOME, USER. CURRENT SYSTEM TIME: {}", started.format("%a %b %e %T %Y"))?;
+        writeln!(self.output, "ESTABLISHING REMOTE LINK...")?;
+        writeln!(self.output, "BEGIN.")
+    }
+
+    fn clear(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn divider(&mut self) -> Result<()> {
+        writeln!(self.output, "-------------------------------------------------------")
+    }
+
+    fn narrative(&mut self, text: &str) -> Result<()> {
+        writeln!(self.output, "{}", text)
+    }
+
+    fn hours(&mut self, hours: i32) -> Result<()> {
+        writeln!(self.output, "{} hours now remain.", hours)
+    }
+
+    fn status(&mut self, _scene: &str, _hours: i32, _decay: f64) -> Result<()> {
+        Ok(())
+    }
+
+    fn choices(&mut self, choices: &[(&str, bool)]) -> Result<()> {
+        writeln!(self.output)?;
+        writeln!(self.output, "What next?")?;
+        for (choice, enabled) in choices {
+            writeln!(self.output, "{}{}", choice, if *enabled { "" } else { " (unavailable)" })?;
+        }
+        Ok(())
+    }
+
+    fn hint(&mut self, _hint: &str) -> Result<()> {
+        Ok(())
+    }
+
+    fn notice(&mut self, message: &str) -> Result<()> {
+        writeln!(self.output, "{}", message)
+    }
+
+    fn error(&mut self, message: &str) -> Result<()> {
+        writeln!(self.output, "{}", message)
+    }
+
+    fn epilogue(&mut self, text: &str) -> Result<()> {
+        writeln!(self.output, "EPILOGUE: {}", text)
+    }
+
+    fn effect(&mut self, _fx: Fx<'_>) -> Result<()> {
+        Ok(())
+    }
+
+    fn ambience(&mut self, _layers: &[Layer]) -> Result<()> {
+        Ok(())
+    }
+
+    fn ending_screen(&mut self) -> Result<()> {
+        writeln!(self.output, "CONSCIOUSNESS TERMINATED")?;
+        writeln!(self.output, "CONNECTION TERMINATED - SESSION LOGS ARCHIVED")
+    }
+
+    fn exit_prompt(&mut self) -> Result<()> {
+        self.output.flush()
+    }
+
+    fn disconnect(&mut self) -> Result<()> {
+        writeln!(self.output, "REMOTE LINK SEVERED BY USER.")?;
+        self.output.flush()
+    }
+
+    fn menu(&mut self, choices: &[(&str, bool)], _hint: &str) -> Result<Input> {
+        self.choices(choices)?;
+        loop {
+            let line = self.read_line()?;
+            match line.to_ascii_lowercase().as_str() {
+                "s" => return Ok(Input::Save),
+                "l" => return Ok(Input::Load),
+                _ if clock::expired() => return Ok(Input::Expired),
+                _ => {}
+            }
+            match line.parse::<usize>() {
+                Ok(n) if n >= 1 && n <= choices.len() && choices[n - 1].1 => {
+                    return Ok(Input::Choice(n as i32))
+                }
+                _ => writeln!(self.output, "{}", INVALID_INPUT)?,
+            }
+        }
+    }
+
+    fn command(&mut self, _verbs: &[String]) -> Result<Input> {
+        let line = self.read_line()?;
+        if clock::expired() {
+            return Ok(Input::Expired);
+        }
+        Ok(Input::Text(line))
+    }
+
+    fn slot(&mut self, action: &str, slots: u8) -> Result<Option<u8>> {
+        writeln!(self.output, "{} WHICH SLOT? (1-{})", action, slots)?;
+        let line = self.read_line()?;
+        Ok(line.parse().ok().filter(|slot| (1..=slots).contains(slot)))
+    }
+}
+
+// Wraps another renderer and keeps an in-memory transcript of everything
+// shown and entered, one event per line. With a path, it is written out to
+// that file when the session is over.
+pub struct Recorder {
+    inner: Box<dyn Renderer>,
+    path: Option<PathBuf>,
+    lines: Vec<String>,
+}
+
+impl Recorder {
+    pub fn new(inner: Box<dyn Renderer>, path: Option<PathBuf>) -> Recorder {
+        Recorder { inner, path, lines: Vec::new() }
+    }
+
+    // Everything recorded so far, e.g. "text: You open your eyes."
+    pub fn events(&self) -> &[String] {
+        &self.lines
+    }
+
+    fn record(&mut self, kind: &str, text: impl AsRef<str>) {
+        match text.as_ref() {
+            "" => self.lines.push(kind.to_string()),
+            text => self.lines.push(format!("{}: {}", kind, text)),
+        }
+    }
+}
+
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let mut transcript = self.events().join("\n");
+        transcript.push('\n');
+        if let Err(e) = fs::write(path, transcript) {
+            eprintln!("could not write transcript to {}: {}", path.display(), e);
+        }
+    }
+}
+
+impl Renderer for Recorder {
+    fn power_on(&mut self) -> Result<()> {
+        self.inner.power_on()
+    }
+
+    fn boot(&mut self, started: &DateTime<Local>) -> Result<()> {
+        self.record("boot", started.to_rfc3339());
+        self.inner.boot(started)
+    }
+
+    fn clear(&mut self) -> Result<()> {
+        self.inner.clear()
+    }
+
+    fn divider(&mut self) -> Result<()> {
+        self.record("divider", "");
+        self.inner.divider()
+    }
+
+    fn narrative(&mut self, text: &str) -> Result<()> {
+        self.record("text", text);
+        self.inner.narrative(text)
+    }
+
+    fn hours(&mut self, hours: i32) -> Result<()> {
+        self.record("hours", hours.to_string());
+        self.inner.hours(hours)
+    }
+
+    fn status(&mut self, scene: &str, hours: i32, decay: f64) -> Result<()> {
+        self.inner.status(scene, hours, decay)
+    }
+
+    fn choices(&mut self, choices: &[(&str, bool)]) -> Result<()> {
+        for (choice, enabled) in choices {
+            self.record(if *enabled { "choice" } else { "choice (unavailable)" }, choice);
+        }
+        self.inner.choices(choices)
+    }
+
+    fn hint(&mut self, hint: &str) -> Result<()> {
+        self.inner.hint(hint)
+    }
+
+    fn notice(&mut self, message: &str) -> Result<()> {
+        self.record("notice", message);
+        self.inner.notice(message)
+    }
+
+    fn error(&mut self, message: &str) -> Result<()> {
+        self.record("error", message);
+        self.inner.error(message)
+    }
+
+    fn epilogue(&mut self, text: &str) -> Result<()> {
+        self.record("epilogue", text);
+        self.inner.epilogue(text)
+    }
+
+    fn effect(&mut self, fx: Fx<'_>) -> Result<()> {
+        self.record("effect", format!("{:?}", fx));
+        self.inner.effect(fx)
+    }
+
+    fn ambience(&mut self, layers: &[Layer]) -> Result<()> {
+        self.record("ambience", format!("{:?}", layers));
+        self.inner.ambience(layers)
+    }
+
+    fn ending_screen(&mut self) -> Result<()> {
+        self.record("ending", "");
+        self.inner.ending_screen()
+    }
+
+    fn exit_prompt(&mut self) -> Result<()> {
+        self.inner.exit_prompt()
+    }
+
+    fn disconnect(&mut self) -> Result<()> {
+        self.record("disconnect", "");
+        self.inner.disconnect()
+    }
+
+    fn menu(&mut self, choices: &[(&str, bool)], hint: &str) -> Result<Input> {
+        for (choice, enabled) in choices {
+            self.record(if *enabled { "choice" } else { "choice (unavailable)" }, choice);
+        }
+        let input = self.inner.menu(choices, hint)?;
+        match &input {
+            Input::Choice(n) => self.record("input", n.to_string()),
+            Input::Save => self.record("input", "save"),
+            Input::Load => self.record("input", "load"),
+            Input::Disconnect => self.record("input", "disconnect"),
+            Input::Expired => self.record("input", "expired"),
+            Input::Text(line) => self.record("input", line),
+        }
+        Ok(input)
+    }
+
+    fn command(&mut self, verbs: &[String]) -> Result<Input> {
+        let input = self.inner.command(verbs)?;
+        match &input {
+            Input::Text(line) => self.record("command", line),
+            Input::Expired => self.record("command", "expired"),
+            _ => self.record("command", "disconnect"),
+        }
+        Ok(input)
+    }
+
+    fn slot(&mut self, action: &str, slots: u8) -> Result<Option<u8>> {
+        let slot = self.inner.slot(action, slots)?;
+        self.record("slot", slot.map_or("cancelled".to_string(), |s| s.to_string()));
+        Ok(slot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::io::Cursor;
+    use std::rc::Rc;
+    use super::*;
+    use crate::narrative::run_game;
+    use crate::session_log::SessionLog;
+    use crate::story::Story;
+
+    // Output that can still be read after it is handed to a renderer
+    #[derive(Clone, Default)]
+    struct Screen(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for Screen {
+        fn write(&mut self, bytes: &[u8]) -> Result<usize> {
+            self.0.borrow_mut().extend_from_slice(bytes);
+            Ok(bytes.len())
+        }
+
+        fn flush(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    // Play the bundled story with `input` typed in, returning the ending
+    // reached, the recorded events and what was shown
+    fn play(input: &str, mode: InputMode) -> (Result<Option<u8>>, Vec<String>, String) {
+        let story = Story::bundled().unwrap();
+        let screen = Screen::default();
+        let plain = PlainRenderer::new(Box::new(Cursor::new(input.to_string())), Box::new(screen.clone()));
+        let mut recorder = Recorder::new(Box::new(plain), None);
+        let ending = run_game(&story, None, &mut recorder, &mut SessionLog::discard(), mode).map(|scene| scene.ending);
+        let shown = String::from_utf8(screen.0.borrow().clone()).unwrap();
+        (ending, recorder.events().to_vec(), shown)
+    }
+
+    // Remain, stand, take steps, keep walking, sit and rest
+    const REST: &str = "1\n2\n2\n2\n1\n";
+
+    // Whether `expected` appear in `events` in this order, not necessarily together
+    fn in_order(events: &[String], expected: &[&str]) -> bool {
+        let mut events = events.iter();
+        expected.iter().all(|e| events.any(|event| event == e))
+    }
+
+    #[test]
+    fn choices_are_played_through_to_an_ending() {
+        let (ending, events, shown) = play(REST, InputMode::Menu);
+        assert_eq!(ending.unwrap(), Some(2));
+        assert!(in_order(&events, &[
+            "ambience: [Wind]",
+            "text: You open your eyes.",
+            "choice: 1. Remain where I am.",
+            "choice: 2. Stand up.",
+            "input: 1",
+            "divider",
+            "text: You remain where you are.",
+            "hours: 9",
+            "input: 2",
+            "ambience: [Wind, Grass]",
+            "hours: 6",
+            "choice: 1. Sit and rest.",
+            "input: 1",
+            "effect: Sound(\"fade\")",
+            "epilogue: ERROR: CONNECTION LOST",
+            "ending",
+        ]));
+        assert_eq!(events.last().unwrap(), "ending");
+        assert!(shown.contains("\nWhat next?\n1. Remain where I am.\n2. Stand up.\n> 1\n"));
+        assert!(shown.ends_with("CONNECTION TERMINATED - SESSION LOGS ARCHIVED\n"));
+    }
+
+    #[test]
+    fn choices_that_cannot_be_picked_are_refused_in_place() {
+        let (ending, events, shown) = play(&format!("3\nzero\n# a comment\n\n{}", REST), InputMode::Menu);
+        assert_eq!(ending.unwrap(), Some(2));
+        assert_eq!(shown.matches(INVALID_INPUT).count(), 2);
+        // Refused input is answered by the menu itself, so the first input
+        // recorded is the one that was accepted
+        assert_eq!(events.iter().find(|e| e.starts_with("input: ")).unwrap(), "input: 1");
+        assert!(!shown.contains("a comment"));
+    }
+
+    #[test]
+    fn running_out_of_choices_is_an_error() {
+        let (ending, events, _) = play("1\n2\n", InputMode::Menu);
+        assert_eq!(ending.unwrap_err().kind(), ErrorKind::UnexpectedEof);
+        assert!(in_order(&events, &["input: 1", "input: 2", "choice: 2. Take a few steps."]));
+        assert!(!events.contains(&"ending".to_string()));
+    }
+
+    #[test]
+    fn parser_mode_answers_what_it_cannot_do() {
+        let input = "again\nsteps remain\nremain where I am\ng\n";
+        let (_, events, _) = play(input, InputMode::Parser);
+        assert!(in_order(&events, &[
+            "command: again",
+            "error: YOU HAVEN'T DONE ANYTHING YET.",
+            "command: steps remain",
+            "error: I DON'T UNDERSTAND THAT.",
+            "command: remain where I am",
+            "text: You remain where you are.",
+            // "g" repeats the last command, which isn't on offer in the next scene
+            "command: g",
+            "error: YOU CAN'T DO THAT RIGHT NOW.",
+        ]));
+    }
+
+    #[test]
+    fn parser_mode_takes_typed_commands() {
+        let input = "remain where I am\nstand up\ntake some steps\nkeep walking\nsit and rest\n";
+        let (ending, events, _) = play(input, InputMode::Parser);
+        assert_eq!(ending.unwrap(), Some(2));
+        assert!(in_order(&events, &[
+            "command: remain where I am",
+            "text: You remain where you are.",
+            "command: stand up",
+            "command: take some steps",
+            "command: keep walking",
+            "command: sit and rest",
+            "ending",
+        ]));
+    }
+}
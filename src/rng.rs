@@ -0,0 +1,94 @@
+use std::cell::RefCell;
+use rand::{Error, RngCore};
+
+// Small SplitMix64 generator whose whole state is one u64, so a saved game
+// can carry it and pick up the same sequence of random events
+#[derive(Debug, Clone)]
+pub struct GameRng {
+    state: u64,
+}
+
+impl GameRng {
+    pub fn from_state(state: u64) -> GameRng {
+        GameRng { state }
+    }
+
+    pub fn state(&self) -> u64 {
+        self.state
+    }
+}
+
+impl RngCore for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let bytes = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+thread_local! {
+    // The one generator behind every random event in the game, from flickers
+    // to phosphor noise and interference tones. Seeded at startup from --seed
+    // or at random, and restored from a save.
+    static GAME_RNG: RefCell<GameRng> = RefCell::new(GameRng::from_state(rand::random()));
+}
+
+fn with<T>(f: impl FnOnce(&mut GameRng) -> T) -> T {
+    GAME_RNG.with(|rng| f(&mut rng.borrow_mut()))
+}
+
+// Handle to the game's generator, used like rand::thread_rng()
+pub struct SharedRng;
+
+pub fn game_rng() -> SharedRng {
+    SharedRng
+}
+
+impl RngCore for SharedRng {
+    fn next_u32(&mut self) -> u32 {
+        with(|rng| rng.next_u32())
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        with(|rng| rng.next_u64())
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        with(|rng| rng.fill_bytes(dest))
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        with(|rng| rng.try_fill_bytes(dest))
+    }
+}
+
+// Start a fresh, reproducible sequence
+pub fn seed(seed: u64) {
+    restore(seed);
+}
+
+pub fn state() -> u64 {
+    with(|rng| rng.state())
+}
+
+pub fn restore(state: u64) {
+    with(|rng| *rng = GameRng::from_state(state));
+}
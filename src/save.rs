@@ -0,0 +1,88 @@
+use std::fs;
+use std::io::{Error, ErrorKind, Result};
+use std::path::PathBuf;
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use crate::clock;
+use crate::rng;
+use crate::state::{GameState, Value};
+use crate::story::HOURS;
+
+// Bump when the layout of SaveGame changes
+const SAVE_VERSION: u32 = 1;
+
+pub const SLOTS: u8 = 3;
+
+// A snapshot of a playthrough, taken at a choice prompt
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SaveGame {
+    pub version: u32,
+    pub scene: String,
+    pub state: GameState,
+    pub rng: u64,
+    pub saved_at: String,
+}
+
+impl SaveGame {
+    pub fn capture(scene: &str, state: &GameState) -> SaveGame {
+        // In countdown mode the clock has been spending the scene's hours too
+        let mut state = state.clone();
+        if let Some(hours) = clock::hours_left() {
+            state.set(HOURS, Value::Int(hours));
+        }
+        SaveGame {
+            version: SAVE_VERSION,
+            scene: scene.to_string(),
+            state,
+            rng: rng::state(),
+            saved_at: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        }
+    }
+
+    pub fn write(&self, slot: u8) -> Result<()> {
+        fs::create_dir_all(save_dir())?;
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(slot_path(slot), json)
+    }
+
+    // The save in a slot, or None if the slot is empty
+    pub fn read(slot: u8) -> Result<Option<SaveGame>> {
+        let json = match fs::read_to_string(slot_path(slot)) {
+            Ok(json) => json,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let save: SaveGame = serde_json::from_str(&json)?;
+        if save.version != SAVE_VERSION {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("save version {} is not supported", save.version),
+            ));
+        }
+        Ok(Some(save))
+    }
+
+    // The most recently written save across all slots
+    pub fn latest() -> Option<(u8, SaveGame)> {
+        (1..=SLOTS)
+            .filter_map(|slot| {
+                let modified = fs::metadata(slot_path(slot)).and_then(|m| m.modified()).ok()?;
+                let save = SaveGame::read(slot).ok()??;
+                Some((modified, slot, save))
+            })
+            .max_by_key(|(modified, _, _)| *modified)
+            .map(|(_, slot, save)| (slot, save))
+    }
+}
+
+// Saves live under the user's data directory, e.g. ~/.local/share/text_adventure
+pub fn save_dir() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("text_adventure")
+        .join("saves")
+}
+
+fn slot_path(slot: u8) -> PathBuf {
+    save_dir().join(format!("slot{}.json", slot))
+}
@@ -0,0 +1,87 @@
+use std::env;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use chrono::{DateTime, Local};
+use serde::Serialize;
+
+// Environment variable that moves the log directory when --log-dir isn't given
+const LOG_DIR_VAR: &str = "TEXT_ADVENTURE_LOG_DIR";
+
+// One line of a session log
+#[derive(Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum LogEvent<'a> {
+    Start { time: String, seed: u64 },
+    Scene { scene: &'a str, hours: i32 },
+    Choice { scene: &'a str, choice: usize, label: &'a str, hours: i32 },
+    Resume { scene: &'a str, hours: i32 },
+    Ending { scene: &'a str, hours: i32 },
+    Disconnect { scene: &'a str, hours: i32 },
+    // The countdown ran out in this scene
+    Expire { scene: &'a str },
+}
+
+// JSON lines record of a single session, one file per session.
+// Logging is best effort: if the file can't be written the game carries on.
+pub struct SessionLog {
+    writer: Option<BufWriter<File>>,
+}
+
+impl SessionLog {
+    pub fn start(dir: Option<PathBuf>, started: DateTime<Local>, seed: u64) -> SessionLog {
+        let dir = dir
+            .or_else(|| env::var_os(LOG_DIR_VAR).map(PathBuf::from))
+            .unwrap_or_else(default_dir);
+        let name = format!("session-{}", started.format("%Y%m%d-%H%M%S"));
+
+        let writer = fs::create_dir_all(&dir)
+            .and_then(|_| create_unique(&dir, &name))
+            .map(BufWriter::new)
+            .ok();
+
+        let mut log = SessionLog { writer };
+        log.record(LogEvent::Start { time: started.to_rfc3339(), seed });
+        log
+    }
+
+    // A log that records nothing, for tests
+    #[cfg(test)]
+    pub fn discard() -> SessionLog {
+        SessionLog { writer: None }
+    }
+
+    pub fn record(&mut self, event: LogEvent) {
+        let Some(writer) = self.writer.as_mut() else {
+            return;
+        };
+        let written = serde_json::to_writer(&mut *writer, &event)
+            .map_err(io::Error::from)
+            .and_then(|_| writeln!(writer))
+            .and_then(|_| writer.flush());
+        if written.is_err() {
+            self.writer = None;
+        }
+    }
+}
+
+// Sessions started in the same second, e.g. scripted headless runs, each get
+// their own file: session-NAME.jsonl, then session-NAME-2.jsonl and so on
+fn create_unique(dir: &Path, name: &str) -> io::Result<File> {
+    let mut suffix = 1;
+    loop {
+        let file = if suffix == 1 { format!("{}.jsonl", name) } else { format!("{}-{}.jsonl", name, suffix) };
+        match OpenOptions::new().write(true).create_new(true).open(dir.join(file)) {
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => suffix += 1,
+            result => return result,
+        }
+    }
+}
+
+// Logs sit beside the saves, e.g. ~/.local/share/text_adventure/logs
+fn default_dir() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("text_adventure")
+        .join("logs")
+}
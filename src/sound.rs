@@ -1,152 +1,129 @@
-use rand::Rng;
-use rodio::{OutputStream, Sink, Source};
 use std::io;
-use std::thread;
+use std::path::Path;
+use std::sync::OnceLock;
 use std::time::Duration;
+use rand::Rng;
+use rodio::source::{Source, Zero};
+use crate::audio;
+use crate::crt_effects;
+use crate::cue::{Cue, CueLibrary, Wave};
+use crate::rng;
+use crate::synth::{Oscillator, Waveform, SAMPLE_RATE};
 
-// PC Speaker tones frequencies (in Hz)
-const PC_BEEP_FREQ: f32 = 800.0; // Standard PC beep
-const PC_ERROR_FREQ: f32 = 400.0; // Lower tone for errors
-const PC_ALERT_FREQ: f32 = 1200.0; // Higher tone for alerts
-//const PC_SUCCESS_FREQ: f32 = 1000.0; // Success tone
-
-// Helper function to play a tone at specified frequency and duration
-fn play_tone(frequency: f32, duration_ms: u64) -> io::Result<()> {
-    // Try to get an output stream handle and sink
-    let (_stream, stream_handle) = match OutputStream::try_default() {
-        Ok(result) => result,
-        Err(_) => return Ok(()), // Silently fail if audio isn't available
-    };
+// Cues the terminal plays itself, whatever the story
+pub const SYSTEM_CUES: &[&str] = &["beep", "error", "alert", "flicker", "fade", "ending", "boot", "connection"];
 
-    let sink = match Sink::try_new(&stream_handle) {
-        Ok(sink) => sink,
-        Err(_) => return Ok(()), // Silently fail if sink creation fails
-    };
+// How far flat a fully decayed speaker plays, in semitones
+const MAX_DETUNE: f32 = 2.0;
 
-    // Create a source with the PC speaker-like square wave
-    let source = rodio::source::SineWave::new(frequency)
-        .take_duration(Duration::from_millis(duration_ms))
-        .amplify(0.20); // Lower volume to avoid being too loud
+// Square waves are loud; a cue at full volume plays at this level
+const SPEAKER_LEVEL: f32 = 0.12;
 
-    // Play the source
-    sink.append(source);
+static CUES: OnceLock<CueLibrary> = OnceLock::new();
 
-    // Wait for the sound to finish playing
-    thread::sleep(Duration::from_millis(duration_ms));
+// Use these cues instead of the bundled ones; only takes effect before the
+// first sound is played
+pub fn set_cues(cues: CueLibrary) {
+    let _ = CUES.set(cues);
+}
+
+fn cues() -> &'static CueLibrary {
+    CUES.get_or_init(|| CueLibrary::bundled().expect("the bundled cues are valid"))
+}
 
+// Queue a cue by name on the audio engine, which plays it in the background
+// while the narrative carries on. Cue names are checked on load.
+pub fn play(name: &str) -> io::Result<()> {
+    if let Some(cue) = cues().get(name) {
+        audio::play(render(cue, SAMPLE_RATE));
+    }
     Ok(())
 }
 
+// Render a cue offline to a 16-bit mono WAV file, through the same synthesis
+// as live playback, to review or test sounds without a speaker. Returns the
+// number of samples written.
+pub fn render_wav(name: &str, sample_rate: u32, path: &Path) -> io::Result<usize> {
+    let cue = cues().get(name).ok_or_else(|| {
+        let names: Vec<&str> = cues().names().collect();
+        let message = format!("unknown sound cue '{}', expected one of: {}", name, names.join(", "));
+        io::Error::new(io::ErrorKind::InvalidInput, message)
+    })?;
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut wav = hound::WavWriter::create(path, spec).map_err(io::Error::other)?;
+    let mut written = 0;
+    for sample in render(cue, sample_rate).into_iter().flatten() {
+        wav.write_sample((sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16).map_err(io::Error::other)?;
+        written += 1;
+    }
+    wav.finalize().map_err(io::Error::other)?;
+    Ok(written)
+}
+
+// A cue as one sound per row, at the given sample rate. The speaker sags out of tune as the display
+// decays, and jitter draws from the game's generator so a seeded run
+// replays the same pitches.
+fn render(cue: &Cue, sample_rate: u32) -> Vec<audio::Sound> {
+    let waveform = match cue.wave {
+        Wave::Square => Waveform::Square,
+        Wave::Pulse => Waveform::Pulse(cue.duty),
+        Wave::Triangle => Waveform::Triangle,
+    };
+    let detune = 2f32.powf(-(crt_effects::decay() as f32 * MAX_DETUNE) / 12.0);
+    let mut rng = rng::game_rng();
+
+    cue.notes
+        .iter()
+        .map(|note| {
+            let duration = Duration::from_millis(note.duration_ms);
+            let Some(frequency) = note.frequency else {
+                return Box::new(Zero::<f32>::new(1, sample_rate).take_duration(duration)) as audio::Sound;
+            };
+            let jitter = if cue.jitter > 0.0 { rng.gen::<f32>() * cue.jitter } else { 0.0 };
+            let source = Oscillator::new(waveform, (frequency + jitter) * detune, duration, sample_rate)
+                .envelope(Duration::from_millis(cue.attack), Duration::from_millis(cue.release))
+                .amplify(SPEAKER_LEVEL * cue.volume);
+            Box::new(source) as audio::Sound
+        })
+        .collect()
+}
+
 // Classic PC beep (higher pitch, short duration)
 pub fn beep() -> io::Result<()> {
-    play_tone(PC_BEEP_FREQ, 150)
+    play("beep")
 }
 
 // Error beep (lower tone)
 pub fn error_sound() -> io::Result<()> {
-    play_tone(PC_ERROR_FREQ, 300)
-}
-
-// // Multiple beeps with delay - classic PC XT style
-// pub fn multi_beep(count: u8, delay_ms: u64) -> io::Result<()> {
-//     for _ in 0..count {
-//         beep()?;
-//         thread::sleep(Duration::from_millis(delay_ms));
-//     }
-//     Ok(())
-// }
-
-// Alert sound - higher pitch beeps
-pub fn alert_sound() -> io::Result<()> {
-    play_tone(PC_ALERT_FREQ, 100)?;
-    thread::sleep(Duration::from_millis(70));
-    play_tone(PC_ALERT_FREQ, 100)
+    play("error")
 }
 
-// Success sound - ascending tones
-// pub fn success_sound() -> io::Result<()> {
-//     play_tone(PC_BEEP_FREQ, 100)?;
-//     thread::sleep(Duration::from_millis(50));
-//     play_tone(PC_SUCCESS_FREQ, 150)
-// }
-
-// Processing sound - repeated tones
-// pub fn processing_sound() -> io::Result<()> {
-//     for i in 0..3 {
-//         // Slightly increase pitch for each beep
-//         play_tone(PC_BEEP_FREQ + (i as f32 * 50.0), 100)?;
-//         thread::sleep(Duration::from_millis(200));
-//     }
-//     Ok(())
-// }
-
 // Sound for terminal/connection flickering - random tones
 pub fn flicker_sound() -> io::Result<()> {
-    let mut rng = rand::thread_rng();
-    // Random frequency between 500-1000 Hz for electrical interference feel
-    let freq = 500.0 + (rng.gen::<f32>() * 500.0);
-    play_tone(freq, 50)
+    play("flicker")
 }
 
 // Sound for when consciousness is fading - descending tones
 pub fn fade_sound() -> io::Result<()> {
-    // Series of progressively lower tones
-    play_tone(1000.0, 200)?;
-    thread::sleep(Duration::from_millis(100));
-    play_tone(800.0, 200)?;
-    thread::sleep(Duration::from_millis(150));
-    play_tone(600.0, 250)?;
-    thread::sleep(Duration::from_millis(200));
-    play_tone(400.0, 300)?;
-    Ok(())
+    play("fade")
 }
 
 // Dramatic sound for ending
 pub fn ending_sound() -> io::Result<()> {
-    // Dramatic arpeggio down
-    play_tone(300.0, 200)?;
-    thread::sleep(Duration::from_millis(100));
-    play_tone(100.0, 200)?;
-    thread::sleep(Duration::from_millis(100));
-    play_tone(600.0, 200)?;
-    thread::sleep(Duration::from_millis(100));
-    play_tone(300.0, 400)?;
-    Ok(())
+    play("ending")
 }
 
 // Classic boot-up chime sound
 pub fn boot_sound() -> io::Result<()> {
-    // Classic PC start sound
-    play_tone(800.0, 150)?;
-    thread::sleep(Duration::from_millis(50));
-    play_tone(1000.0, 150)?;
-    thread::sleep(Duration::from_millis(50));
-    play_tone(1200.0, 200)?;
-    Ok(())
+    play("boot")
 }
 
-// Error/crash sound
-// pub fn crash_sound() -> io::Result<()> {
-//     play_tone(800.0, 100)?;
-//     play_tone(700.0, 100)?;
-//     play_tone(600.0, 100)?;
-//     play_tone(500.0, 100)?;
-//     play_tone(400.0, 400)?;
-//     Ok(())
-// }
-
 // A dial-up modem-like sound for "establishing connection"
 pub fn connection_sound() -> io::Result<()> {
-    // Brief dial-up modem simulation
-    for i in 0..5 {
-        let freq = 500.0 + (i as f32 * 100.0);
-        play_tone(freq, 70)?;
-        thread::sleep(Duration::from_millis(30));
-    }
-
-    // Connection established tone
-    thread::sleep(Duration::from_millis(200));
-    play_tone(900.0, 200)?;
-
-    Ok(())
+    play("connection")
 }
@@ -0,0 +1,161 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use serde::{Deserialize, Serialize};
+
+// A typed story variable
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Value {
+    Int(i32),
+    Bool(bool),
+    Str(String),
+}
+
+impl Value {
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Int(_) => "int",
+            Value::Bool(_) => "bool",
+            Value::Str(_) => "string",
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Int(n) => write!(f, "{}", n),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Str(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+// A change to a variable, written in story files as "name = value",
+// "name += n" or "name -= n"
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Effect {
+    pub var: String,
+    pub op: Op,
+}
+
+#[derive(Debug, Clone)]
+pub enum Op {
+    Set(Value),
+    Add(i32),
+}
+
+impl TryFrom<String> for Effect {
+    type Error = String;
+
+    fn try_from(source: String) -> Result<Effect, String> {
+        let eq = source
+            .find('=')
+            .ok_or_else(|| format!("'{}' is not an assignment", source))?;
+        let (var, op) = match source[..eq].chars().last() {
+            Some('+') => (&source[..eq - 1], "+="),
+            Some('-') => (&source[..eq - 1], "-="),
+            _ => (&source[..eq], "="),
+        };
+        let value = source[eq + 1..].trim();
+
+        let var = var.trim();
+        if var.is_empty() || !var.chars().all(|c| c.is_alphanumeric() || c == '_') {
+            return Err(format!("'{}' is not a valid variable name", var));
+        }
+
+        let value = parse_literal(value)
+            .ok_or_else(|| format!("'{}' is not a number, true/false or a quoted string", value))?;
+
+        let op = match (op, value) {
+            ("=", value) => Op::Set(value),
+            ("+=", Value::Int(n)) => Op::Add(n),
+            ("-=", Value::Int(n)) => Op::Add(-n),
+            _ => return Err(format!("'{}' only adds or subtracts numbers", source)),
+        };
+
+        Ok(Effect { var: var.to_string(), op })
+    }
+}
+
+fn parse_literal(text: &str) -> Option<Value> {
+    match text {
+        "true" => Some(Value::Bool(true)),
+        "false" => Some(Value::Bool(false)),
+        _ => {
+            if let Some(s) = text.strip_prefix('"').and_then(|s| s.strip_suffix('"')) {
+                Some(Value::Str(s.to_string()))
+            } else {
+                text.parse().ok().map(Value::Int)
+            }
+        }
+    }
+}
+
+// Everything the story knows about the current playthrough
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct GameState {
+    vars: BTreeMap<String, Value>,
+    visited: BTreeSet<String>,
+}
+
+impl GameState {
+    pub fn new(vars: &BTreeMap<String, Value>) -> GameState {
+        GameState {
+            vars: vars.clone(),
+            visited: BTreeSet::new(),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.vars.get(name)
+    }
+
+    // Integer variable, or 0 if it is missing or not a number
+    pub fn int(&self, name: &str) -> i32 {
+        match self.vars.get(name) {
+            Some(Value::Int(n)) => *n,
+            _ => 0,
+        }
+    }
+
+    pub fn set(&mut self, name: &str, value: Value) {
+        self.vars.insert(name.to_string(), value);
+    }
+
+    pub fn apply(&mut self, effect: &Effect) {
+        match &effect.op {
+            Op::Set(value) => {
+                self.vars.insert(effect.var.clone(), value.clone());
+            }
+            Op::Add(n) => {
+                let current = self.int(&effect.var);
+                self.vars.insert(effect.var.clone(), Value::Int(current + n));
+            }
+        }
+    }
+
+    pub fn apply_all(&mut self, effects: &[Effect]) {
+        for effect in effects {
+            self.apply(effect);
+        }
+    }
+
+    pub fn visit(&mut self, scene: &str) {
+        self.visited.insert(scene.to_string());
+    }
+
+    pub fn has_visited(&self, scene: &str) -> bool {
+        self.visited.contains(scene)
+    }
+
+    // Replace each {name} in the text with the variable's value
+    pub fn interpolate(&self, text: &str) -> String {
+        let mut result = text.to_string();
+        for (name, value) in &self.vars {
+            result = result.replace(&format!("{{{}}}", name), &value.to_string());
+        }
+        result
+    }
+}
@@ -0,0 +1,328 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::fs;
+use std::io::{Error, ErrorKind, Result};
+use std::path::Path;
+use serde::de::value::MapAccessDeserializer;
+use serde::de::{self, IntoDeserializer, MapAccess, Visitor};
+use serde::{Deserialize, Deserializer};
+use crate::condition::{Condition, Reference};
+use crate::state::{Effect, Op, Value};
+
+// The variable that counts down the player's remaining time
+pub const HOURS: &str = "hours";
+
+// A headless run exits with status 10 + n for ending n, which has to fit in
+// the byte an exit status gets
+pub const MAX_ENDING: u8 = 245;
+
+// The story that ships with the game, used when no --story file is given
+const BUNDLED_STORY: &str = include_str!("../stories/consciousness.toml");
+
+// A complete story: a graph of scenes joined by choices
+#[derive(Debug, Deserialize)]
+pub struct Story {
+    pub start: String,
+    // Ending played when the clock runs out in countdown mode
+    #[serde(default)]
+    pub expire: Option<String>,
+    // Starting values; every variable a story uses must be declared here
+    pub variables: BTreeMap<String, Value>,
+    pub scenes: Vec<Scene>,
+    #[serde(skip)]
+    index: HashMap<String, usize>,
+}
+
+// A single scene: what happens on entry, what is shown, and where it can lead
+#[derive(Debug, Deserialize)]
+pub struct Scene {
+    pub id: String,
+    // Applied each time the scene is entered
+    #[serde(default)]
+    pub enter: Vec<Effect>,
+    #[serde(default)]
+    pub body: Vec<Step>,
+    // A scene without choices is an ending
+    #[serde(default)]
+    pub choices: Vec<Choice>,
+    // Number that identifies an ending, e.g. in a headless run's exit status
+    #[serde(default)]
+    pub ending: Option<u8>,
+    // Background sound to fade to on entry; if left out, whatever is playing
+    // carries on, and an empty list fades it all out
+    #[serde(default)]
+    pub ambience: Option<Vec<Layer>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Choice {
+    pub label: String,
+    pub goto: String,
+    // Only shown while this condition holds
+    #[serde(default)]
+    pub when: Option<Condition>,
+    // Shown greyed out, and cannot be picked, unless this condition holds
+    #[serde(default)]
+    pub enabled: Option<Condition>,
+    // Applied when the choice is taken
+    #[serde(default)]
+    pub set: Vec<Effect>,
+    // Other ways to type this choice in parser mode, besides its label
+    #[serde(default)]
+    pub phrases: Vec<String>,
+}
+
+// One beat of a scene body, played in order
+#[derive(Debug)]
+pub enum Step {
+    Command(Command),
+    Text {
+        text: String,
+        when: Option<Condition>,
+    },
+    Epilogue { epilogue: String },
+    // Light flicker with the given probability (1.0 = always)
+    Flicker {
+        flicker: f64,
+        when: Option<Condition>,
+    },
+    // A sound cue, by name
+    Sound { sound: String },
+    Pause { pause: u64 },
+}
+
+// A step is either a bare command name or a table with one kind of key.
+// Deserialized by hand, rather than as an untagged enum, so a bad condition or
+// a misspelled key is reported as such instead of "did not match any variant".
+impl<'de> Deserialize<'de> for Step {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Step, D::Error> {
+        struct StepVisitor;
+
+        impl<'de> Visitor<'de> for StepVisitor {
+            type Value = Step;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "a command such as \"clear\", or a table such as {{ text = \"...\" }}")
+            }
+
+            fn visit_str<E: de::Error>(self, name: &str) -> std::result::Result<Step, E> {
+                Command::deserialize(name.into_deserializer()).map(Step::Command)
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, map: A) -> std::result::Result<Step, A::Error> {
+                StepTable::deserialize(MapAccessDeserializer::new(map))?
+                    .into_step()
+                    .map_err(de::Error::custom)
+            }
+        }
+
+        deserializer.deserialize_any(StepVisitor)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct StepTable {
+    text: Option<String>,
+    epilogue: Option<String>,
+    flicker: Option<f64>,
+    sound: Option<String>,
+    pause: Option<u64>,
+    when: Option<Condition>,
+}
+
+impl StepTable {
+    fn into_step(self) -> std::result::Result<Step, String> {
+        let when = self.when;
+        let step = match (self.text, self.epilogue, self.flicker, self.sound, self.pause) {
+            (Some(text), None, None, None, None) => return Ok(Step::Text { text, when }),
+            (None, None, Some(flicker), None, None) => return Ok(Step::Flicker { flicker, when }),
+            (None, Some(epilogue), None, None, None) => Step::Epilogue { epilogue },
+            (None, None, None, Some(sound), None) => Step::Sound { sound },
+            (None, None, None, None, Some(pause)) => Step::Pause { pause },
+            (None, None, None, None, None) => {
+                return Err("a step needs one of text, epilogue, flicker, sound or pause".to_string())
+            }
+            _ => return Err("a step can only be one of text, epilogue, flicker, sound or pause".to_string()),
+        };
+        if when.is_some() {
+            return Err("only text and flicker steps can have a `when` condition".to_string());
+        }
+        Ok(step)
+    }
+}
+
+// Looping background sounds a scene can ask for
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Layer {
+    Wind,
+    Grass,
+    Birds,
+    Insects,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Command {
+    Clear,
+    Divider,
+    FlickerCheck,
+    Hours,
+    EndingScreen,
+}
+
+impl Story {
+    pub fn bundled() -> Result<Story> {
+        Story::parse(BUNDLED_STORY)
+    }
+
+    pub fn load(path: &Path) -> Result<Story> {
+        let source = fs::read_to_string(path)?;
+        Story::parse(&source).map_err(|e| {
+            Error::new(e.kind(), format!("{}: {}", path.display(), e))
+        })
+    }
+
+    pub fn parse(source: &str) -> Result<Story> {
+        let mut story: Story = toml::from_str(source)
+            .map_err(|e| Error::new(ErrorKind::InvalidData, e.to_string()))?;
+
+        for (i, scene) in story.scenes.iter().enumerate() {
+            if story.index.insert(scene.id.clone(), i).is_some() {
+                return Err(invalid(format!("duplicate scene '{}'", scene.id)));
+            }
+        }
+
+        // The remaining hours drive the whole game
+        if !matches!(story.variables.get(HOURS), Some(Value::Int(_))) {
+            return Err(invalid(format!("'{}' must be declared as a number in [variables]", HOURS)));
+        }
+
+        // Every transition must land on a scene that exists
+        if !story.index.contains_key(&story.start) {
+            return Err(invalid(format!("start scene '{}' does not exist", story.start)));
+        }
+        if let Some(expire) = &story.expire {
+            match story.index.get(expire) {
+                None => return Err(invalid(format!("expire scene '{}' does not exist", expire))),
+                Some(&i) if !story.scenes[i].is_ending() => {
+                    return Err(invalid(format!("expire scene '{}' must be an ending", expire)));
+                }
+                _ => {}
+            }
+        }
+        for scene in &story.scenes {
+            story.check_effects(&scene.id, &scene.enter)?;
+
+            match (scene.is_ending(), scene.ending) {
+                (true, None) => {
+                    return Err(invalid(format!("scene '{}' has no choices, so it needs an ending number", scene.id)));
+                }
+                (false, Some(_)) => {
+                    return Err(invalid(format!("scene '{}' has an ending number but also has choices", scene.id)));
+                }
+                (_, Some(n)) if n > MAX_ENDING => {
+                    return Err(invalid(format!("scene '{}' has ending number {}, but the highest is {}", scene.id, n, MAX_ENDING)));
+                }
+                _ => {}
+            }
+
+            for step in &scene.body {
+                if let Step::Text { when, .. } | Step::Flicker { when, .. } = step {
+                    story.check_condition(&scene.id, when.as_ref())?;
+                }
+            }
+
+            for choice in &scene.choices {
+                if !story.index.contains_key(&choice.goto) {
+                    return Err(invalid(format!(
+                        "scene '{}': choice '{}' leads to unknown scene '{}'",
+                        scene.id, choice.label, choice.goto
+                    )));
+                }
+                story.check_effects(&scene.id, &choice.set)?;
+                story.check_condition(&scene.id, choice.when.as_ref())?;
+                story.check_condition(&scene.id, choice.enabled.as_ref())?;
+            }
+        }
+
+        Ok(story)
+    }
+
+    pub fn scene(&self, id: &str) -> Option<&Scene> {
+        self.index.get(id).map(|&i| &self.scenes[i])
+    }
+
+    // The hours the player starts with
+    pub fn stay(&self) -> i32 {
+        match self.variables.get(HOURS) {
+            Some(Value::Int(n)) => *n,
+            _ => 0,
+        }
+    }
+
+    // How much of the stay has run out with `hours` left: 0.0 at the start,
+    // 1.0 with none left. Drives the display's decay.
+    pub fn decay(&self, hours: i32) -> f64 {
+        if self.stay() <= 0 {
+            return 1.0;
+        }
+        (1.0 - hours as f64 / self.stay() as f64).clamp(0.0, 1.0)
+    }
+
+    // Conditions may only read declared variables and existing scenes
+    fn check_condition(&self, scene: &str, condition: Option<&Condition>) -> Result<()> {
+        let Some(condition) = condition else {
+            return Ok(());
+        };
+        for reference in condition.references() {
+            match reference {
+                Reference::Var(var) if !self.variables.contains_key(var) => {
+                    return Err(invalid(format!(
+                        "scene '{}': condition '{}' reads undeclared variable '{}'",
+                        scene, condition, var
+                    )));
+                }
+                Reference::Scene(id) if !self.index.contains_key(id) => {
+                    return Err(invalid(format!(
+                        "scene '{}': condition '{}' refers to unknown scene '{}'",
+                        scene, condition, id
+                    )));
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    // Effects may only change declared variables, and must keep their type
+    fn check_effects(&self, scene: &str, effects: &[Effect]) -> Result<()> {
+        for effect in effects {
+            let declared = self.variables.get(&effect.var).ok_or_else(|| {
+                invalid(format!("scene '{}': variable '{}' is not declared", scene, effect.var))
+            })?;
+            let assigned = match &effect.op {
+                Op::Set(value) => value.type_name(),
+                Op::Add(_) => "int",
+            };
+            if assigned != declared.type_name() {
+                return Err(invalid(format!(
+                    "scene '{}': '{}' is {}, not {}",
+                    scene, effect.var, declared.type_name(), assigned
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Scene {
+    pub fn is_ending(&self) -> bool {
+        self.choices.is_empty()
+    }
+}
+
+fn invalid(message: String) -> Error {
+    Error::new(ErrorKind::InvalidData, message)
+}
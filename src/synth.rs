@@ -0,0 +1,156 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use rodio::Source;
+
+// The rate sounds are played at; offline renders can choose another
+pub const SAMPLE_RATE: u32 = 44100;
+
+// The IBM PC's 8253 timer counts down from this input clock; the speaker can
+// only play the clock divided by a whole number between 1 and 65535
+const PIT_CLOCK_HZ: f32 = 1_193_182.0;
+
+// Whether tones snap to the frequencies the 8253 can actually produce
+static PIT: AtomicBool = AtomicBool::new(false);
+
+pub fn set_pit_quantisation(enabled: bool) {
+    PIT.store(enabled, Ordering::Relaxed);
+}
+
+// The nearest frequency the 8253 can produce, e.g. 800 Hz comes out as
+// 1193182 / 1491 = 800.25 Hz
+pub fn pit_frequency(frequency: f32) -> f32 {
+    let divisor = (PIT_CLOCK_HZ / frequency).round().clamp(1.0, 65535.0);
+    PIT_CLOCK_HZ / divisor
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Waveform {
+    // The speaker's native sound: on half the time, off the other half
+    Square,
+    // On for the given fraction of each cycle; thinner and buzzier than square
+    Pulse(f32),
+    // Softer, with far weaker overtones
+    Triangle,
+}
+
+// A band-limited oscillator. Square and pulse waves have their edges
+// smoothed with PolyBLEP so high notes don't alias into whistles; the
+// triangle is the integral of the smoothed square.
+pub struct Oscillator {
+    waveform: Waveform,
+    // Phase advance per sample, in cycles
+    step: f32,
+    phase: f32,
+    // Integrator state for the triangle
+    integral: f32,
+    // Samples taken to fade in and out, to soften the edges of a note
+    attack: usize,
+    release: usize,
+    length: usize,
+    remaining: usize,
+    duration: Duration,
+    sample_rate: u32,
+}
+
+impl Oscillator {
+    pub fn new(waveform: Waveform, frequency: f32, duration: Duration, sample_rate: u32) -> Oscillator {
+        let frequency = if PIT.load(Ordering::Relaxed) { pit_frequency(frequency) } else { frequency };
+        let length = samples(duration, sample_rate);
+        Oscillator {
+            waveform,
+            step: (frequency / sample_rate as f32).min(0.5),
+            phase: 0.0,
+            integral: 0.0,
+            attack: 0,
+            release: 0,
+            length,
+            remaining: length,
+            duration,
+            sample_rate,
+        }
+    }
+
+    // Fade in over `attack` and out over `release`, rather than switching
+    // the speaker straight on and off
+    pub fn envelope(mut self, attack: Duration, release: Duration) -> Oscillator {
+        self.attack = samples(attack, self.sample_rate);
+        self.release = samples(release, self.sample_rate);
+        self
+    }
+
+    fn pulse(&self, duty: f32) -> f32 {
+        let naive = if self.phase < duty { 1.0 } else { -1.0 };
+        naive + poly_blep(self.phase, self.step) - poly_blep((self.phase + 1.0 - duty) % 1.0, self.step)
+    }
+}
+
+fn samples(duration: Duration, sample_rate: u32) -> usize {
+    (duration.as_secs_f64() * sample_rate as f64).round() as usize
+}
+
+// Correction around a discontinuity at phase 0, for a wave advancing `step`
+// cycles per sample
+fn poly_blep(phase: f32, step: f32) -> f32 {
+    if phase < step {
+        let t = phase / step;
+        t + t - t * t - 1.0
+    } else if phase > 1.0 - step {
+        let t = (phase - 1.0) / step;
+        t * t + t + t + 1.0
+    } else {
+        0.0
+    }
+}
+
+impl Iterator for Oscillator {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let position = self.length - self.remaining;
+        self.remaining -= 1;
+        let mut gain = 1.0;
+        if position < self.attack {
+            gain *= position as f32 / self.attack as f32;
+        }
+        if self.remaining < self.release {
+            gain *= self.remaining as f32 / self.release as f32;
+        }
+
+        let sample = match self.waveform {
+            Waveform::Square => self.pulse(0.5),
+            Waveform::Pulse(duty) => self.pulse(duty.clamp(0.01, 0.99)),
+            Waveform::Triangle => {
+                // Leaky, so any offset from starting mid-cycle dies away
+                self.integral = self.step * self.pulse(0.5) + (1.0 - self.step) * self.integral;
+                self.integral * 4.0
+            }
+        };
+
+        self.phase += self.step;
+        if self.phase >= 1.0 {
+            self.phase -= 1.0;
+        }
+        Some(sample * gain)
+    }
+}
+
+impl Source for Oscillator {
+    fn current_frame_len(&self) -> Option<usize> {
+        Some(self.remaining)
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        Some(self.duration)
+    }
+}
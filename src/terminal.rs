@@ -0,0 +1,75 @@
+use std::io::{self, Result};
+use std::panic;
+use std::process;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use crossterm::{
+    cursor,
+    event::DisableMouseCapture,
+    execute,
+    style::{Print, ResetColor},
+    terminal::{self, Clear, ClearType},
+};
+use signal_hook::consts::{SIGINT, SIGTERM};
+use signal_hook::iterator::Signals;
+
+// Whether the terminal is currently in raw mode on the alternate screen
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+// Puts the terminal into the state the CRT display needs, and puts it back
+// however the game ends: normally, by panic, or by SIGINT/SIGTERM
+pub struct TerminalGuard;
+
+impl TerminalGuard {
+    pub fn enter() -> Result<TerminalGuard> {
+        // Restore before the panic message is printed, so it stays readable
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            restore();
+            previous(info);
+        }));
+
+        // In raw mode Ctrl-C arrives as a key press, but a signal can still
+        // come from elsewhere, e.g. kill or closing the window
+        let mut signals = Signals::new([SIGINT, SIGTERM])?;
+        thread::spawn(move || {
+            if let Some(signal) = signals.forever().next() {
+                restore();
+                process::exit(128 + signal);
+            }
+        });
+
+        terminal::enable_raw_mode()?;
+        ACTIVE.store(true, Ordering::SeqCst);
+        execute!(
+            io::stdout(),
+            terminal::EnterAlternateScreen,
+            Clear(ClearType::All),
+            cursor::Hide
+        )?;
+        Ok(TerminalGuard)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        restore();
+    }
+}
+
+// Leave the alternate screen and raw mode; safe to call more than once
+pub fn restore() {
+    if ACTIVE.swap(false, Ordering::SeqCst) {
+        let _ = execute!(
+            io::stdout(),
+            ResetColor,
+            // The status bar's scroll region
+            Print("\x1b[r"),
+            DisableMouseCapture,
+            cursor::SetCursorStyle::DefaultUserShape,
+            cursor::Show,
+            terminal::LeaveAlternateScreen
+        );
+        let _ = terminal::disable_raw_mode();
+    }
+}
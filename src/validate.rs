@@ -0,0 +1,349 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::io::{Result, Write};
+
+use crate::state::{GameState, Value};
+use crate::story::{Scene, Story, HOURS};
+
+// Give up rather than run forever on a story whose variables never settle
+const MAX_STATES: usize = 100_000;
+
+// One reachable point in a playthrough: a scene just entered, and the state
+// on entering it
+struct Node<'a> {
+    scene: &'a Scene,
+    state: GameState,
+    // The node this was first reached from
+    parent: Option<usize>,
+    // Each choice that can be picked here, by index, and the node it leads to
+    next: Vec<(usize, usize)>,
+}
+
+// Play every choice in every reachable state, evaluating conditions exactly
+// as the game does, and report what the story can and cannot do. Returns the
+// number of problems found; warnings are reported but not counted.
+pub fn validate(story: &Story, out: &mut dyn Write) -> Result<usize> {
+    let (nodes, complete) = explore(story);
+    let mut problems = Vec::new();
+    let mut warnings = Vec::new();
+
+    if !complete {
+        problems.push(format!(
+            "more than {} distinct states; the results below are incomplete",
+            MAX_STATES
+        ));
+    }
+
+    // Scenes no playthrough can enter
+    let reached: BTreeSet<&str> = nodes.iter().map(|n| n.scene.id.as_str()).collect();
+    for scene in &story.scenes {
+        if !reached.contains(scene.id.as_str()) {
+            problems.push(format!("scene '{}' is unreachable", scene.id));
+        }
+    }
+
+    // Choices that are never both shown and enabled
+    let taken: BTreeSet<(&str, usize)> = nodes
+        .iter()
+        .flat_map(|n| n.next.iter().map(|&(choice, _)| (n.scene.id.as_str(), choice)))
+        .collect();
+    for scene in story.scenes.iter().filter(|s| reached.contains(s.id.as_str())) {
+        for (index, choice) in scene.choices.iter().enumerate() {
+            if !taken.contains(&(scene.id.as_str(), index)) {
+                problems.push(format!(
+                    "scene '{}': choice '{}' can never be picked",
+                    scene.id, choice.label
+                ));
+            }
+        }
+    }
+
+    // States from which no ending can be reached, reported once per scene
+    let can_end = can_end(&nodes);
+    let mut stuck: BTreeMap<&str, usize> = BTreeMap::new();
+    for (i, node) in nodes.iter().enumerate() {
+        if !can_end[i] {
+            stuck.entry(node.scene.id.as_str()).or_insert(i);
+        }
+    }
+    for (scene, &i) in &stuck {
+        let reason = if nodes[i].next.is_empty() { "no choice can be picked" } else { "no ending can be reached" };
+        problems.push(format!("scene '{}': {}, e.g. via {}", scene, reason, path(&nodes, i)));
+    }
+
+    // Hours on arrival at each scene, over every path that reaches it
+    let mut hours: BTreeMap<&str, (i32, i32)> = BTreeMap::new();
+    for node in &nodes {
+        let h = node.state.int(HOURS);
+        let range = hours.entry(node.scene.id.as_str()).or_insert((h, h));
+        range.0 = range.0.min(h);
+        range.1 = range.1.max(h);
+    }
+    if let Some(i) = (0..nodes.len()).min_by_key(|&i| nodes[i].state.int(HOURS)) {
+        let lowest = nodes[i].state.int(HOURS);
+        if lowest < 0 {
+            warnings.push(format!("{} falls to {} via {}", HOURS, lowest, path(&nodes, i)));
+        }
+    }
+
+    let endings = story.scenes.iter().filter(|s| s.is_ending()).count();
+    writeln!(out, "{} scenes, {} endings, {} reachable states", story.scenes.len(), endings, nodes.len())?;
+    writeln!(out)?;
+    writeln!(out, "Hours on arrival (min..max):")?;
+    for scene in &story.scenes {
+        if let Some((min, max)) = hours.get(scene.id.as_str()) {
+            let ending = scene.ending.map_or(String::new(), |n| format!("  [ending {}]", n));
+            let line = format!("  {:<24} {:>3}..{:<3}{}", scene.id, min, max, ending);
+            writeln!(out, "{}", line.trim_end())?;
+        }
+    }
+    writeln!(out)?;
+
+    for warning in &warnings {
+        writeln!(out, "warning: {}", warning)?;
+    }
+    if problems.is_empty() {
+        writeln!(out, "No problems found.")?;
+    } else {
+        writeln!(out, "{} problem(s):", problems.len())?;
+        for problem in &problems {
+            writeln!(out, "  {}", problem)?;
+        }
+    }
+
+    Ok(problems.len())
+}
+
+// Breadth-first search over (scene, state) pairs. Returns false as the second
+// value if the search was cut short.
+fn explore(story: &Story) -> (Vec<Node<'_>>, bool) {
+    let start = story.scene(&story.start).expect("start scene is checked on load");
+    let mut state = GameState::new(&story.variables);
+    state.apply_all(&start.enter);
+    state.visit(&start.id);
+
+    let mut nodes = vec![Node { scene: start, state: state.clone(), parent: None, next: Vec::new() }];
+    let mut seen: HashMap<(&str, GameState), usize> = HashMap::new();
+    seen.insert((start.id.as_str(), state), 0);
+    let mut queue = VecDeque::from([0]);
+
+    // In countdown mode the expire scene can be entered from anywhere, once
+    // the clock has run out
+    if let Some(expire) = story.expire.as_deref().and_then(|id| story.scene(id)) {
+        let mut state = GameState::new(&story.variables);
+        state.set(HOURS, Value::Int(0));
+        state.apply_all(&expire.enter);
+        state.visit(&expire.id);
+        nodes.push(Node { scene: expire, state, parent: None, next: Vec::new() });
+    }
+
+    while let Some(i) = queue.pop_front() {
+        let scene = nodes[i].scene;
+        for (index, choice) in scene.choices.iter().enumerate() {
+            let state = &nodes[i].state;
+            let shown = choice.when.as_ref().is_none_or(|c| c.eval(state));
+            let enabled = choice.enabled.as_ref().is_none_or(|c| c.eval(state));
+            if !shown || !enabled {
+                continue;
+            }
+
+            // Same order as the game: the choice's effects, then the next scene's
+            let target = story.scene(&choice.goto).expect("choice targets are checked on load");
+            let mut next = state.clone();
+            next.apply_all(&choice.set);
+            next.apply_all(&target.enter);
+            next.visit(&target.id);
+
+            let j = match seen.get(&(target.id.as_str(), next.clone())) {
+                Some(&j) => j,
+                None => {
+                    if nodes.len() >= MAX_STATES {
+                        return (nodes, false);
+                    }
+                    let j = nodes.len();
+                    seen.insert((target.id.as_str(), next.clone()), j);
+                    nodes.push(Node {
+                        scene: target,
+                        state: next,
+                        parent: Some(i),
+                        next: Vec::new(),
+                    });
+                    queue.push_back(j);
+                    j
+                }
+            };
+            nodes[i].next.push((index, j));
+        }
+    }
+
+    (nodes, true)
+}
+
+// Which nodes have some way forward to an ending
+fn can_end(nodes: &[Node]) -> Vec<bool> {
+    let mut can_end: Vec<bool> = nodes.iter().map(|n| n.scene.is_ending()).collect();
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (i, node) in nodes.iter().enumerate() {
+            if !can_end[i] && node.next.iter().any(|&(_, j)| can_end[j]) {
+                can_end[i] = true;
+                changed = true;
+            }
+        }
+    }
+    can_end
+}
+
+// The first path found to a node, e.g. "awaken -> laying_path -> who_am_i"
+fn path(nodes: &[Node], mut i: usize) -> String {
+    let mut scenes = vec![nodes[i].scene.id.as_str()];
+    while let Some(parent) = nodes[i].parent {
+        i = parent;
+        scenes.push(nodes[i].scene.id.as_str());
+    }
+    scenes.reverse();
+    scenes.join(" -> ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Validate a story, returning the problem count and the report
+    fn check(source: &str) -> (usize, String) {
+        let story = Story::parse(source).unwrap();
+        let mut out = Vec::new();
+        let problems = validate(&story, &mut out).unwrap();
+        (problems, String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn a_sound_story_has_no_problems() {
+        let (problems, report) = check(
+            r#"
+            start = "start"
+            [variables]
+            hours = 3
+            [[scenes]]
+            id = "start"
+            choices = [{ label = "Go.", goto = "end" }]
+            [[scenes]]
+            id = "end"
+            ending = 1
+            "#,
+        );
+        assert_eq!(problems, 0, "{}", report);
+        assert!(report.starts_with("2 scenes, 1 endings, 2 reachable states\n"), "{}", report);
+        assert!(report.ends_with("No problems found.\n"), "{}", report);
+    }
+
+    #[test]
+    fn unreachable_scenes_are_problems() {
+        let (problems, report) = check(
+            r#"
+            start = "start"
+            [variables]
+            hours = 3
+            [[scenes]]
+            id = "start"
+            choices = [{ label = "Go.", goto = "end" }]
+            [[scenes]]
+            id = "attic"
+            choices = [{ label = "Go.", goto = "end" }]
+            [[scenes]]
+            id = "end"
+            ending = 1
+            "#,
+        );
+        assert_eq!(problems, 1, "{}", report);
+        assert!(report.contains("  scene 'attic' is unreachable\n"), "{}", report);
+        // Nor does it get hours, never being arrived at
+        assert!(!report.contains("  attic "), "{}", report);
+    }
+
+    #[test]
+    fn choices_that_can_never_be_picked_are_problems() {
+        let (problems, report) = check(
+            r#"
+            start = "start"
+            [variables]
+            hours = 3
+            key = false
+            [[scenes]]
+            id = "start"
+            choices = [
+                { label = "Unlock the door.", goto = "end", when = "key" },
+                { label = "Knock.", goto = "end", enabled = "hours > 5" },
+                { label = "Leave.", goto = "end" },
+            ]
+            [[scenes]]
+            id = "end"
+            ending = 1
+            "#,
+        );
+        assert_eq!(problems, 2, "{}", report);
+        assert!(report.contains("  scene 'start': choice 'Unlock the door.' can never be picked\n"), "{}", report);
+        assert!(report.contains("  scene 'start': choice 'Knock.' can never be picked\n"), "{}", report);
+    }
+
+    #[test]
+    fn scenes_with_no_way_to_an_ending_are_problems() {
+        let (problems, report) = check(
+            r#"
+            start = "start"
+            [variables]
+            hours = 3
+            lost = false
+            [[scenes]]
+            id = "start"
+            choices = [
+                { label = "Wander.", goto = "maze", set = ["lost = true"] },
+                { label = "Leave.", goto = "end" },
+            ]
+            [[scenes]]
+            id = "maze"
+            choices = [
+                { label = "Turn.", goto = "maze" },
+                { label = "Leave.", goto = "end", when = "!lost" },
+            ]
+            [[scenes]]
+            id = "end"
+            ending = 1
+            "#,
+        );
+        assert_eq!(problems, 2, "{}", report);
+        assert!(report.contains("  scene 'maze': no ending can be reached, e.g. via start -> maze\n"), "{}", report);
+        assert!(report.contains("  scene 'maze': choice 'Leave.' can never be picked\n"), "{}", report);
+    }
+
+    #[test]
+    fn hours_are_reported_over_every_path() {
+        let (problems, report) = check(
+            r#"
+            start = "start"
+            [variables]
+            hours = 4
+            [[scenes]]
+            id = "start"
+            enter = ["hours -= 1"]
+            choices = [
+                { label = "Rest.", goto = "end", set = ["hours += 2"] },
+                { label = "Run.", goto = "field" },
+            ]
+            [[scenes]]
+            id = "field"
+            enter = ["hours -= 5"]
+            choices = [{ label = "Stop.", goto = "end" }]
+            [[scenes]]
+            id = "end"
+            ending = 7
+            "#,
+        );
+        // Running out of time is a warning, not a problem
+        assert_eq!(problems, 0, "{}", report);
+        assert!(report.contains("\n  start                      3..3\n"), "{}", report);
+        assert!(report.contains("\n  field                     -2..-2\n"), "{}", report);
+        assert!(report.contains("\n  end                       -2..5    [ending 7]\n"), "{}", report);
+        assert!(report.contains("\nwarning: hours falls to -2 via start -> field\n"), "{}", report);
+    }
+}
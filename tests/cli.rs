@@ -0,0 +1,163 @@
+// The command line: subcommands, and how errors are reported.
+
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+fn run(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_text_adventure"))
+        .args(args)
+        .output()
+        .expect("the game runs")
+}
+
+// A story file in a temporary directory, removed when dropped
+struct TempStory(PathBuf);
+
+impl TempStory {
+    fn new(name: &str, source: &str) -> TempStory {
+        let path = std::env::temp_dir().join(format!("text_adventure_{}_{}.toml", name, std::process::id()));
+        fs::write(&path, source).unwrap();
+        TempStory(path)
+    }
+
+    fn path(&self) -> &str {
+        self.0.to_str().unwrap()
+    }
+}
+
+impl Drop for TempStory {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+#[test]
+fn errors_are_shown_as_messages() {
+    let story = TempStory::new("broken", "start = \"a\"\n[variables]\nhours = 1\n[[scenes]]\nid = \"a\"\nbody = [{ txt = \"Hello\" }]\n");
+    let output = run(&["--story", story.path()]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    // The parser's caret display survives, on its own lines
+    assert!(stderr.contains("line 6"), "{}", stderr);
+    assert!(stderr.contains("\n  |"), "{}", stderr);
+    assert!(stderr.contains("unknown field `txt`"), "{}", stderr);
+    assert!(!stderr.contains("Custom {"), "{}", stderr);
+}
+
+#[test]
+fn bad_arguments_are_shown_as_messages() {
+    let output = run(&["export-graph", "--format", "png"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "Error: unknown graph format 'png', expected dot or mermaid\n"
+    );
+}
+
+// Parses, but has a problem of every kind validate looks for
+const FLAWED_STORY: &str = r#"
+start = "start"
+
+[variables]
+hours = 2
+key = false
+
+[[scenes]]
+id = "start"
+enter = ["hours -= 1"]
+choices = [
+    { label = "Open the door.", goto = "door", when = "key" },
+    { label = "Wait.", goto = "wait" },
+    { label = "Leave.", goto = "end" },
+]
+
+[[scenes]]
+id = "door"
+ending = 1
+
+[[scenes]]
+id = "wait"
+choices = [{ label = "Keep waiting.", goto = "wait" }]
+
+[[scenes]]
+id = "end"
+enter = ["hours -= 3"]
+ending = 0
+"#;
+
+#[test]
+fn validate_runs_after_flags() {
+    let story = TempStory::new("flawed", FLAWED_STORY);
+    for args in [["--story", story.path(), "validate"], ["validate", "--story", story.path()]] {
+        let output = run(&args);
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert_eq!(output.status.code(), Some(1), "{:?}: {}", args, stdout);
+        assert!(stdout.contains("3 problem(s):"), "{}", stdout);
+        assert!(stdout.contains("scene 'door' is unreachable"), "{}", stdout);
+        assert!(stdout.contains("scene 'start': choice 'Open the door.' can never be picked"), "{}", stdout);
+        assert!(stdout.contains("scene 'wait': no ending can be reached, e.g. via start -> wait"), "{}", stdout);
+        assert!(stdout.contains("warning: hours falls to -2 via start -> end"), "{}", stdout);
+    }
+}
+
+#[test]
+fn the_bundled_story_validates() {
+    let output = run(&["--seed", "1", "validate"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(String::from_utf8(output.stdout).unwrap().contains("No problems found."));
+}
+
+#[test]
+fn unknown_commands_are_refused() {
+    let output = run(&["--headless", "valdate"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "Error: unknown command 'valdate', expected validate, export-graph or render-cue\n"
+    );
+
+    let output = run(&["export-graph", "extra"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(String::from_utf8(output.stderr).unwrap(), "Error: unexpected argument 'extra'\n");
+}
+
+// Play headless with `input`, returning the exit status and the last line of
+// the session log
+fn play_headless(name: &str, args: &[&str], input: &str) -> (Option<i32>, String) {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let log_dir = std::env::temp_dir().join(format!("text_adventure_{}_{}", name, std::process::id()));
+    let mut child = Command::new(env!("CARGO_BIN_EXE_text_adventure"))
+        .args(["--headless", "--seed", "1"])
+        .args(args)
+        .arg("--log-dir")
+        .arg(&log_dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("the game runs");
+    child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+    let status = child.wait().unwrap();
+
+    let log = fs::read_dir(&log_dir).unwrap().next().unwrap().unwrap().path();
+    let last = fs::read_to_string(log).unwrap().lines().last().unwrap().to_string();
+    let _ = fs::remove_dir_all(&log_dir);
+    (status.code(), last)
+}
+
+#[test]
+fn quitting_headless_has_its_own_exit_status() {
+    let (code, last) = play_headless("quit", &["--parser"], "stand up\nquit\n");
+    assert_eq!(code, Some(3));
+    assert_eq!(last, r#"{"event":"disconnect","scene":"stand_up","hours":11}"#);
+}
+
+#[test]
+fn running_out_of_input_is_logged_as_a_disconnect() {
+    let (code, last) = play_headless("input_ended", &[], "1\n");
+    assert_eq!(code, Some(2));
+    assert_eq!(last, r#"{"event":"disconnect","scene":"laying_path","hours":9}"#);
+}
@@ -0,0 +1,205 @@
+// Parity with the original C version in legacy/main.c.
+//
+// Each file in tests/parity is one path through the C game, played headless:
+// its header gives the choices and the ending they reach, and the rest is the
+// transcript expected on stdout, minus the first line with the system time.
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+// Lines the Rust version has added since the port, which the C never printed
+const ADDED_SINCE_PORT: &[&str] = &["Well, as much as it can be.", "ERROR: CONNECTION LOST"];
+
+// Text in main.c that no transcript shows: the greeting is checked on its own
+// before the transcript starts, the C's link banner became the headless frame,
+// and a refused choice makes the C replay the whole scene, which no path
+// through it can match
+const NOT_IN_TRANSCRIPTS: &[&str] = &[
+    "WELCOME, USER. CURRENT SYSTEM TIME:",
+    "REMOTE LINK SUCCESSFUL.",
+    "BEGIN:",
+    "REMOTE LINK ERROR: USER INPUT INVALID. TRY AGAIN.",
+];
+
+// The C's "EPILOGUE:" heading; the port marks each epilogue line instead
+const LEGACY_EPILOGUE: &str = "EPILOGUE:";
+
+// Marks epilogue lines in headless output; the text after it is checked like
+// any other line
+const EPILOGUE_PREFIX: &str = "EPILOGUE: ";
+
+// Lines that belong to the headless frame rather than the story
+const FRAME_PREFIXES: &[&str] = &[
+    "> ",
+    "ESTABLISHING REMOTE LINK...",
+    "BEGIN.",
+    "CONSCIOUSNESS TERMINATED",
+    "CONNECTION TERMINATED",
+];
+
+struct Transcript {
+    name: String,
+    choices: Vec<String>,
+    ending: i32,
+    expected: String,
+}
+
+fn transcripts() -> Vec<Transcript> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/parity");
+    let mut paths: Vec<PathBuf> = fs::read_dir(&dir)
+        .expect("tests/parity exists")
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "txt"))
+        .collect();
+    paths.sort();
+
+    paths
+        .iter()
+        .map(|path| {
+            let source = fs::read_to_string(path).unwrap();
+            let mut lines = source.lines();
+            let choices = lines
+                .next()
+                .and_then(|l| l.strip_prefix("# Choices in legacy/main.c: "))
+                .expect("first line lists the choices");
+            let ending = lines
+                .next()
+                .and_then(|l| l.strip_prefix("# Ending: "))
+                .expect("second line gives the ending");
+            let expected: String = lines.map(|l| format!("{}\n", l)).collect();
+            Transcript {
+                name: path.file_stem().unwrap().to_string_lossy().into_owned(),
+                choices: choices.split_whitespace().map(str::to_string).collect(),
+                ending: ending.parse().unwrap(),
+                expected,
+            }
+        })
+        .collect()
+}
+
+// Play the bundled story headless with the given choices; returns stdout and
+// the exit code
+fn play(choices: &[String]) -> (String, i32) {
+    let log_dir = std::env::temp_dir().join("text_adventure_parity_logs");
+    let mut child = Command::new(env!("CARGO_BIN_EXE_text_adventure"))
+        .args(["--headless", "--seed", "0", "--log-dir"])
+        .arg(&log_dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("the game runs");
+    let input: String = choices.iter().map(|c| format!("{}\n", c)).collect();
+    child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+    let output = child.wait_with_output().unwrap();
+    (String::from_utf8(output.stdout).unwrap(), output.status.code().unwrap_or(-1))
+}
+
+// Collapse whitespace and stand in for numbers, so "9 hours now remain."
+// compares equal to the C's "%d hours now remain."
+fn normalise(text: &str) -> String {
+    let mut out = String::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c.is_ascii_digit() || (c == '-' && chars.peek().is_some_and(|n| n.is_ascii_digit())) {
+            while chars.peek().is_some_and(|n| n.is_ascii_digit()) {
+                chars.next();
+            }
+            out.push_str("%d");
+        } else {
+            out.push(c);
+        }
+    }
+    out.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// Every string literal in main.c, normalised, in source order
+fn legacy_literals() -> Vec<String> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("legacy/main.c");
+    let source = fs::read_to_string(path).unwrap();
+    let mut literals = Vec::new();
+    let mut chars = source.chars();
+    while let Some(c) = chars.next() {
+        if c != '"' {
+            continue;
+        }
+        let mut literal = String::new();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => break,
+                '\\' => match chars.next() {
+                    Some('n') => literal.push(' '),
+                    Some(escaped) => literal.push(escaped),
+                    None => {}
+                },
+                _ => literal.push(c),
+            }
+        }
+        literals.push(normalise(&literal));
+    }
+    literals
+}
+
+// Everything main.c prints, as one normalised string
+fn legacy_text() -> String {
+    legacy_literals().join(" ")
+}
+
+#[test]
+fn every_c_path_plays_the_same() {
+    let transcripts = transcripts();
+    assert!(!transcripts.is_empty(), "no transcripts in tests/parity");
+    for transcript in transcripts {
+        let (stdout, code) = play(&transcript.choices);
+        let (first, rest) = stdout.split_once('\n').unwrap_or((&stdout, ""));
+        assert!(first.starts_with("WELCOME, USER. CURRENT SYSTEM TIME: "), "{}: {}", transcript.name, first);
+        assert_eq!(rest, transcript.expected, "{}: transcript differs", transcript.name);
+        assert_eq!(code, 10 + transcript.ending, "{}: wrong ending", transcript.name);
+    }
+}
+
+#[test]
+fn every_line_comes_from_the_c_version() {
+    let legacy = legacy_text();
+    for transcript in transcripts() {
+        for line in transcript.expected.lines() {
+            let line = line.strip_prefix(EPILOGUE_PREFIX).unwrap_or(line);
+            if line.is_empty()
+                || FRAME_PREFIXES.iter().any(|p| line.starts_with(p))
+                || ADDED_SINCE_PORT.contains(&line)
+            {
+                continue;
+            }
+            assert!(legacy.contains(&normalise(line)), "{}: not in legacy/main.c: {}", transcript.name, line);
+        }
+    }
+}
+
+#[test]
+fn every_c_line_is_played_somewhere() {
+    let played: Vec<String> = transcripts()
+        .iter()
+        .map(|t| {
+            let lines = t
+                .expected
+                .lines()
+                .map(|line| line.strip_prefix(EPILOGUE_PREFIX).unwrap_or(line))
+                .filter(|line| !ADDED_SINCE_PORT.contains(line));
+            normalise(&lines.collect::<Vec<_>>().join("\n"))
+        })
+        .collect();
+    // The C's rules of '=' are decoration, so the text either side of one is
+    // looked for separately
+    let missing: Vec<String> = legacy_literals()
+        .iter()
+        .flat_map(|literal| literal.split('='))
+        .map(|piece| piece.trim())
+        .map(|piece| piece.strip_prefix(LEGACY_EPILOGUE).unwrap_or(piece).trim())
+        .filter(|piece| !piece.is_empty() && !NOT_IN_TRANSCRIPTS.contains(piece))
+        .filter(|piece| !played.iter().any(|text| text.contains(piece)))
+        .map(str::to_string)
+        .collect();
+    assert!(missing.is_empty(), "never played by any transcript:\n{}", missing.join("\n"));
+}
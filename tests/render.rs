@@ -0,0 +1,78 @@
+// Offline renders of the bundled sound cues.
+//
+// Each cue is rendered to WAV with a fixed seed and its samples hashed, so any
+// change to how a cue sounds shows up here. When a change is intended, update
+// the hash from the failure message.
+
+use std::path::Path;
+use std::process::Command;
+
+const SAMPLE_RATE: u32 = 22050;
+
+// Cue, length in samples at SAMPLE_RATE, and FNV-1a hash of the samples
+const EXPECTED: &[(&str, usize, u64)] = &[
+    ("alert", 5953, 0x65c023cdc0f35e1d),
+    ("beep", 3308, 0x8195f0dd87d9ee8c),
+    ("boot", 13230, 0xa22b0a86de210789),
+    ("connection", 19845, 0x8d8d1450e1fe197f),
+    ("crash", 17640, 0x00d754f0f4c3e927),
+    ("ending", 28665, 0x19e11bcb1975b6a4),
+    ("error", 6615, 0x7cc0df50d10764ee),
+    ("fade", 30870, 0xa9b83ce691f595f1),
+    ("flicker", 1103, 0x302c05d6193c29f0),
+    ("processing", 19845, 0x6ea43762a050eee2),
+    ("success", 6615, 0xb329582e383daddb),
+];
+
+fn render(cue: &str, dir: &Path) -> Vec<i16> {
+    let path = dir.join(format!("{}.wav", cue));
+    let status = Command::new(env!("CARGO_BIN_EXE_text_adventure"))
+        .args(["render-cue", cue, "--seed", "7", "--sample-rate", &SAMPLE_RATE.to_string(), "--output"])
+        .arg(&path)
+        .output()
+        .expect("the game runs")
+        .status;
+    assert!(status.success(), "rendering {} failed", cue);
+
+    let mut wav = hound::WavReader::open(&path).unwrap();
+    let spec = wav.spec();
+    assert_eq!((spec.channels, spec.sample_rate, spec.bits_per_sample), (1, SAMPLE_RATE, 16));
+    wav.samples::<i16>().map(Result::unwrap).collect()
+}
+
+// FNV-1a, which unlike std's hasher is the same on every platform and release
+fn fnv1a(samples: &[i16]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in samples.iter().flat_map(|s| s.to_le_bytes()) {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
+
+#[test]
+fn cues_render_as_before() {
+    let dir = std::env::temp_dir().join(format!("text_adventure_cues_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let mut changed = Vec::new();
+    for &(cue, length, hash) in EXPECTED {
+        let samples = render(cue, &dir);
+        let actual = (samples.len(), fnv1a(&samples));
+        if actual != (length, hash) {
+            changed.push(format!("    (\"{}\", {}, {:#018x}),", cue, actual.0, actual.1));
+        }
+    }
+    let _ = std::fs::remove_dir_all(&dir);
+    assert!(changed.is_empty(), "these cues render differently now:\n{}", changed.join("\n"));
+}
+
+#[test]
+fn same_seed_renders_the_same_jitter() {
+    let dir = std::env::temp_dir().join(format!("text_adventure_jitter_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let first = render("flicker", &dir);
+    let second = render("flicker", &dir);
+    let _ = std::fs::remove_dir_all(&dir);
+    assert_eq!(first, second);
+}